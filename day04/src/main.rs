@@ -1,32 +1,96 @@
-use std::cmp::{max, min};
-use std::fs;
+mod rendering;
+mod scheduler;
+
 use std::ops::RangeInclusive;
+use std::{env, fs};
 
 use nom::character::complete::{char, digit1, line_ending};
-use nom::multi::many1;
+use nom::combinator::verify;
+use nom::multi::{many1, separated_list1};
 use nom::sequence::{separated_pair, terminated};
 use nom::{IResult, Parser};
 
+use scheduler::{ReassignError, SectionScheduler};
+
 fn main() {
     let input = fs::read_to_string("input/day4.txt").unwrap();
-    let range_pairs = parse_input(&input);
+    let range_groups = parse_input(&input);
+
+    // usage: render [<max columns>], draws the groups instead of solving the puzzle
+    let args: Vec<_> = env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("render") {
+        let max_columns = args.get(1).map_or(100, |arg| {
+            arg.parse().expect("Expected a number of columns")
+        });
+
+        print!(
+            "{}",
+            rendering::GanttRenderer::new(max_columns).render_groups(&range_groups)
+        );
+        return;
+    }
 
-    let num_fully_covering = range_pairs
+    let num_fully_covering = range_groups
         .iter()
-        .filter(|range_pair| range_pair.one_range_fully_covers_other())
+        .filter(|range_group| range_group.one_range_fully_covers_others())
         .count();
 
-    println!("Number of range pairs where one fully covers the other: {num_fully_covering}");
+    println!("Number of range groups where one fully covers the others: {num_fully_covering}");
 
-    let num_overlapping = range_pairs
+    let num_overlapping = range_groups
         .iter()
-        .filter(|range_pair| range_pair.ranges_overlap())
+        .filter(|range_group| range_group.any_ranges_overlap())
         .count();
 
-    println!("Number of range pairs which are overlapping: {num_overlapping}");
+    println!("Number of range groups with at least one overlapping pair: {num_overlapping}");
+
+    let num_sharing_section = range_groups
+        .iter()
+        .filter(|range_group| range_group.all_ranges_share_section())
+        .count();
+
+    println!("Number of range groups where all ranges share a section: {num_sharing_section}");
+
+    // only the sections that appear somewhere in the input are known to exist
+    let first_section = range_groups
+        .iter()
+        .flat_map(|range_group| range_group.ranges.iter().map(|range| *range.start()))
+        .min()
+        .unwrap();
+    let last_section = range_groups
+        .iter()
+        .flat_map(|range_group| range_group.ranges.iter().map(|range| *range.end()))
+        .max()
+        .unwrap();
+    let scheduler = SectionScheduler::new(first_section..=last_section);
+
+    let mut total_shift = 0;
+    let mut num_infeasible = 0;
+    let mut num_too_large = 0;
+    for range_group in range_groups
+        .iter()
+        .filter(|range_group| range_group.any_ranges_overlap())
+    {
+        match scheduler.reassign(range_group) {
+            Ok(reassignment) => total_shift += reassignment.total_shift,
+            Err(ReassignError::Infeasible { .. }) => num_infeasible += 1,
+            Err(ReassignError::TooManyRanges { .. }) => num_too_large += 1,
+        }
+    }
+
+    println!("Total shift distance to remove all feasible overlaps: {total_shift}");
+    println!("Number of overlapping range groups that cannot be reassigned: {num_infeasible}");
+
+    if num_too_large > 0 {
+        println!(
+            "Number of overlapping range groups with more than {} ranges that were skipped: \
+             {num_too_large}",
+            scheduler::MAX_NUM_RANGES
+        );
+    }
 }
 
-fn parse_input(input: &str) -> Vec<RangePair> {
+fn parse_input(input: &str) -> Vec<RangeGroup> {
     fn number(input: &str) -> IResult<&str, u32> {
         digit1
             .map(|digits: &str| digits.parse().expect("Failed to parse number"))
@@ -39,33 +103,84 @@ fn parse_input(input: &str) -> Vec<RangePair> {
             .parse(input)
     }
 
-    let range_pair = separated_pair(range, char(','), range)
-        .map(|(range1, range2)| RangePair { range1, range2 });
-    let range_pair_line = terminated(range_pair, line_ending);
-    let (rest, range_pairs) = many1(range_pair_line).parse(input).unwrap();
+    // a group needs at least two ranges for the predicates to be meaningful
+    let ranges = verify(separated_list1(char(','), range), |ranges: &Vec<_>| {
+        ranges.len() >= 2
+    });
+    let range_group = ranges.map(|ranges| RangeGroup { ranges });
+    let range_group_line = terminated(range_group, line_ending);
+    let (rest, range_groups) = many1(range_group_line).parse(input).unwrap();
 
     assert!(rest.is_empty());
-    range_pairs
+    range_groups
 }
 
 type SectionRange = RangeInclusive<u32>;
 
-struct RangePair {
-    range1: SectionRange,
-    range2: SectionRange,
+struct RangeGroup {
+    ranges: Vec<SectionRange>,
 }
 
-impl RangePair {
-    fn one_range_fully_covers_other(&self) -> bool {
-        range_fully_covers_other(&self.range1, &self.range2)
-            || range_fully_covers_other(&self.range2, &self.range1)
+impl RangeGroup {
+    fn one_range_fully_covers_others(&self) -> bool {
+        self.ranges.iter().any(|covering_range| {
+            self.ranges
+                .iter()
+                .all(|covered_range| range_fully_covers_other(covering_range, covered_range))
+        })
     }
 
-    fn ranges_overlap(&self) -> bool {
-        max(self.range1.start(), self.range2.start()) <= min(self.range1.end(), self.range2.end())
+    fn any_ranges_overlap(&self) -> bool {
+        let mut sorted_ranges: Vec<_> = self.ranges.iter().collect();
+        sorted_ranges.sort_by_key(|range| range.start());
+
+        // after sorting by start, some pair overlaps iff a range starts before
+        // the furthest end seen so far
+        let mut max_end_so_far = sorted_ranges[0].end();
+
+        for range in &sorted_ranges[1..] {
+            if range.start() <= max_end_so_far {
+                return true;
+            }
+
+            max_end_so_far = max_end_so_far.max(range.end());
+        }
+
+        false
+    }
+
+    fn all_ranges_share_section(&self) -> bool {
+        let max_start = self.ranges.iter().map(|range| range.start()).max();
+        let min_end = self.ranges.iter().map(|range| range.end()).min();
+
+        max_start <= min_end
     }
 }
 
 fn range_fully_covers_other(covering_range: &SectionRange, covered_range: &SectionRange) -> bool {
     covering_range.start() <= covered_range.start() && covering_range.end() >= covered_range.end()
 }
+
+mod tests {
+    #[test]
+    fn group_predicates() {
+        let range_groups = super::parse_input("2-4,6-8\n2-8,3-7,5-6\n1-3,3-5,7-9\n4-6,5-9,6-6\n");
+
+        let fully_covering: Vec<_> = range_groups
+            .iter()
+            .map(|range_group| range_group.one_range_fully_covers_others())
+            .collect();
+        let overlapping: Vec<_> = range_groups
+            .iter()
+            .map(|range_group| range_group.any_ranges_overlap())
+            .collect();
+        let sharing_section: Vec<_> = range_groups
+            .iter()
+            .map(|range_group| range_group.all_ranges_share_section())
+            .collect();
+
+        assert_eq!(fully_covering, [false, true, false, false]);
+        assert_eq!(overlapping, [false, true, true, true]);
+        assert_eq!(sharing_section, [false, true, false, true]);
+    }
+}
@@ -0,0 +1,177 @@
+use std::fmt::Write;
+
+use crate::{range_fully_covers_other, RangeGroup, SectionRange};
+
+const FREE: char = '.';
+const ASSIGNED: char = '#';
+const OVERLAPPING: char = 'X';
+
+// Draws range groups like the puzzle text, one row per range. Sections that are
+// shared with another range of the same group are drawn as 'X' and ranges that are
+// fully covered by another range of their group are marked at the end of their row.
+pub struct GanttRenderer {
+    max_columns: usize,
+}
+
+impl GanttRenderer {
+    pub fn new(max_columns: usize) -> Self {
+        assert!(max_columns > 0, "Need at least one column to draw into");
+        GanttRenderer { max_columns }
+    }
+
+    // all groups share one section axis, so they can be compared by eye
+    pub fn render_groups(&self, groups: &[RangeGroup]) -> String {
+        let mut s = String::new();
+
+        let Some(axis) = section_axis(groups) else {
+            return s;
+        };
+
+        let span = (axis.end() - axis.start()) as usize + 1;
+        let sections_per_column = span.div_ceil(self.max_columns);
+
+        if sections_per_column > 1 {
+            writeln!(
+                s,
+                "(sections {}-{}, each column spans {sections_per_column} sections)",
+                axis.start(),
+                axis.end()
+            )
+            .unwrap();
+        }
+
+        for (group_idx, group) in groups.iter().enumerate() {
+            if group_idx > 0 {
+                writeln!(s).unwrap();
+            }
+
+            for (range_idx, range) in group.ranges.iter().enumerate() {
+                let row = self.render_row(group, range_idx, &axis, sections_per_column);
+                write!(s, "{row}  {}-{}", range.start(), range.end()).unwrap();
+
+                if is_covered_by_other(group, range_idx) {
+                    write!(s, " (covered)").unwrap();
+                }
+
+                writeln!(s).unwrap();
+            }
+        }
+
+        s
+    }
+
+    fn render_row(
+        &self,
+        group: &RangeGroup,
+        range_idx: usize,
+        axis: &SectionRange,
+        sections_per_column: usize,
+    ) -> String {
+        let range = &group.ranges[range_idx];
+
+        let overlapping_parts: Vec<_> = group
+            .ranges
+            .iter()
+            .enumerate()
+            .filter(|&(other_idx, _)| other_idx != range_idx)
+            .filter_map(|(_, other)| intersection(range, other))
+            .collect();
+
+        // a column gets the most notable symbol of any of its sections
+        let axis_start = *axis.start() as u64;
+        let axis_end = *axis.end() as u64;
+        let num_columns = (axis_end - axis_start + 1).div_ceil(sections_per_column as u64);
+
+        (0..num_columns)
+            .map(|column_idx| {
+                let column_start = axis_start + column_idx * sections_per_column as u64;
+                let column_end = (column_start + sections_per_column as u64 - 1).min(axis_end);
+                let touches_column = |part: &SectionRange| {
+                    *part.start() as u64 <= column_end && *part.end() as u64 >= column_start
+                };
+
+                if overlapping_parts.iter().any(touches_column) {
+                    OVERLAPPING
+                } else if touches_column(range) {
+                    ASSIGNED
+                } else {
+                    FREE
+                }
+            })
+            .collect()
+    }
+}
+
+fn intersection(range: &SectionRange, other: &SectionRange) -> Option<SectionRange> {
+    let start = *range.start().max(other.start());
+    let end = *range.end().min(other.end());
+
+    (start <= end).then_some(start..=end)
+}
+
+fn section_axis(groups: &[RangeGroup]) -> Option<SectionRange> {
+    let ranges = groups.iter().flat_map(|group| &group.ranges);
+
+    // sections are counted from 1 in the puzzle
+    let first_section = ranges.clone().map(|range| *range.start()).min()?.min(1);
+    let last_section = ranges.map(|range| *range.end()).max()?;
+
+    Some(first_section..=last_section)
+}
+
+fn is_covered_by_other(group: &RangeGroup, range_idx: usize) -> bool {
+    let range = &group.ranges[range_idx];
+
+    group
+        .ranges
+        .iter()
+        .enumerate()
+        .any(|(other_idx, other)| other_idx != range_idx && range_fully_covers_other(other, range))
+}
+
+mod tests {
+    #[test]
+    fn render_example_groups() {
+        let groups = crate::parse_input("2-4,6-8\n2-8,3-7\n5-7,7-9\n");
+        let rendering = super::GanttRenderer::new(80).render_groups(&groups);
+
+        let expected = "\
+.###.....  2-4
+.....###.  6-8
+
+.#XXXXX#.  2-8
+..XXXXX..  3-7 (covered)
+
+....##X..  5-7
+......X##  7-9
+";
+
+        assert_eq!(rendering, expected);
+    }
+
+    #[test]
+    fn render_scaled_group() {
+        let groups = crate::parse_input("1-40,31-60\n");
+        let rendering = super::GanttRenderer::new(6).render_groups(&groups);
+
+        let expected = "\
+(sections 1-60, each column spans 10 sections)
+###X..  1-40
+...X##  31-60
+";
+
+        assert_eq!(rendering, expected);
+
+        // the columns are checked as intervals, so this does not go through every section
+        let groups = crate::parse_input("1-4000000000,3999999999-4000000000\n");
+        let rendering = super::GanttRenderer::new(4).render_groups(&groups);
+
+        let expected = "\
+(sections 1-4000000000, each column spans 1000000000 sections)
+###X  1-4000000000
+...X  3999999999-4000000000 (covered)
+";
+
+        assert_eq!(rendering, expected);
+    }
+}
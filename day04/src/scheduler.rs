@@ -0,0 +1,325 @@
+use std::fmt::Display;
+
+use crate::{RangeGroup, SectionRange};
+
+pub struct SectionScheduler {
+    section_limits: SectionRange,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Reassignment {
+    // in the same order as the ranges of the group
+    pub ranges: Vec<SectionRange>,
+    pub total_shift: u32,
+}
+
+// The search over the orders of the ranges is exponential, with pruning it takes a few
+// milliseconds for ten ranges. The rosters have at most eight elves.
+pub const MAX_NUM_RANGES: usize = 10;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ReassignError {
+    Infeasible {
+        required_sections: u64,
+        available_sections: u64,
+    },
+    TooManyRanges {
+        num_ranges: usize,
+    },
+}
+
+impl Display for ReassignError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReassignError::Infeasible {
+                required_sections,
+                available_sections,
+            } => write!(
+                f,
+                "group needs {required_sections} sections, but only {available_sections} are available"
+            ),
+            ReassignError::TooManyRanges { num_ranges } => write!(
+                f,
+                "group has {num_ranges} ranges, but at most {MAX_NUM_RANGES} can be reassigned"
+            ),
+        }
+    }
+}
+
+impl SectionScheduler {
+    pub fn new(section_limits: SectionRange) -> Self {
+        SectionScheduler { section_limits }
+    }
+
+    // Every range always keeps its length, ranges are never shortened. So a group is
+    // infeasible exactly when the lengths do not fit into the section limits next to
+    // each other, and it is reported as such instead of being solved partially.
+    pub fn reassign(&self, group: &RangeGroup) -> Result<Reassignment, ReassignError> {
+        if group.ranges.len() > MAX_NUM_RANGES {
+            return Err(ReassignError::TooManyRanges {
+                num_ranges: group.ranges.len(),
+            });
+        }
+
+        let starts: Vec<_> = group.ranges.iter().map(|r| *r.start() as i64).collect();
+        let lengths: Vec<_> = group.ranges.iter().map(range_length).collect();
+
+        let lower_limit = *self.section_limits.start() as i64;
+        let available_sections = range_length(&self.section_limits) as u64;
+        let required_sections = lengths.iter().map(|&length| length as u64).sum();
+
+        if required_sections > available_sections {
+            return Err(ReassignError::Infeasible {
+                required_sections,
+                available_sections,
+            });
+        }
+
+        // free room for the position of the first range in any order
+        let upper_limit = lower_limit + (available_sections - required_sections) as i64;
+
+        // The groups are small, so every order of the elves can be tried. Starting with
+        // the sorted order makes it win ties.
+        let mut sorted_indices: Vec<_> = (0..group.ranges.len()).collect();
+        sorted_indices.sort_by_key(|&i| starts[i]);
+
+        let mut search = OrderSearch {
+            starts: &starts,
+            lengths: &lengths,
+            lower_limit,
+            upper_limit,
+            best: None,
+        };
+        search.extend_order(&sorted_indices, &mut Vec::new(), 0, &[]);
+        let (best_cost, best_order) = search.best.expect("There is always at least one order");
+
+        let (best_starts, _) =
+            place_in_order(&best_order, &starts, &lengths, lower_limit, upper_limit);
+
+        let mut ranges = group.ranges.clone();
+        for (i, new_start) in best_order.into_iter().zip(best_starts) {
+            let new_start = new_start as u32;
+            ranges[i] = new_start..=new_start + lengths[i] - 1;
+        }
+
+        Ok(Reassignment {
+            ranges,
+            total_shift: best_cost as u32,
+        })
+    }
+}
+
+// Builds the orders one range at a time with the same pooling as place_in_order. The
+// cost of a prefix is a lower bound for every order that starts with it, because
+// dropping the later ranges from a placement keeps it valid, so most orders are pruned.
+struct OrderSearch<'a> {
+    starts: &'a [i64],
+    lengths: &'a [u32],
+    lower_limit: i64,
+    upper_limit: i64,
+    best: Option<(i64, Vec<usize>)>,
+}
+
+// sorted values that are placed at the same (clamped) median
+struct Block {
+    sorted_values: Vec<i64>,
+    cost: i64,
+}
+
+impl OrderSearch<'_> {
+    fn extend_order(
+        &mut self,
+        remaining: &[usize],
+        order: &mut Vec<usize>,
+        offset: i64,
+        blocks: &[Block],
+    ) {
+        let cost: i64 = blocks.iter().map(|block| block.cost).sum();
+        if self
+            .best
+            .as_ref()
+            .is_some_and(|(best_cost, _)| cost >= *best_cost)
+        {
+            return;
+        }
+
+        if remaining.is_empty() {
+            self.best = Some((cost, order.clone()));
+            return;
+        }
+
+        for (remaining_idx, &i) in remaining.iter().enumerate() {
+            let mut next_blocks: Vec<_> = blocks
+                .iter()
+                .map(|block| Block {
+                    sorted_values: block.sorted_values.clone(),
+                    cost: block.cost,
+                })
+                .collect();
+            let mut block = vec![self.starts[i] - offset];
+
+            while let Some(previous_block) = next_blocks.last() {
+                if median(&previous_block.sorted_values) <= median(&block) {
+                    break;
+                }
+
+                let mut merged = next_blocks.pop().unwrap().sorted_values;
+                merged.extend(block);
+                merged.sort_unstable();
+                block = merged;
+            }
+
+            let value = median(&block).clamp(self.lower_limit, self.upper_limit);
+            let block_cost = block.iter().map(|target| (value - target).abs()).sum();
+            next_blocks.push(Block {
+                sorted_values: block,
+                cost: block_cost,
+            });
+
+            let mut next_remaining = remaining.to_vec();
+            next_remaining.remove(remaining_idx);
+
+            order.push(i);
+            self.extend_order(
+                &next_remaining,
+                order,
+                offset + self.lengths[i] as i64,
+                &next_blocks,
+            );
+            order.pop();
+        }
+    }
+}
+
+fn range_length(range: &SectionRange) -> u32 {
+    range.end() - range.start() + 1
+}
+
+// Places the ranges in the given order next to each other such that the sum of
+// start shifts is minimal. Subtracting the lengths of all earlier ranges from each
+// start turns the non-overlap constraint into a monotonicity constraint, so the
+// problem becomes L1 isotonic regression, which is solved by pooling adjacent
+// violators into blocks at their median. Clamping the result to the limits keeps
+// it optimal.
+fn place_in_order(
+    order: &[usize],
+    starts: &[i64],
+    lengths: &[u32],
+    lower_limit: i64,
+    upper_limit: i64,
+) -> (Vec<i64>, i64) {
+    let mut offsets = Vec::with_capacity(order.len());
+    let mut offset = 0;
+    for &i in order {
+        offsets.push(offset);
+        offset += lengths[i] as i64;
+    }
+
+    let targets: Vec<_> = order
+        .iter()
+        .zip(&offsets)
+        .map(|(&i, offset)| starts[i] - offset)
+        .collect();
+
+    // each block holds its sorted values
+    let mut blocks: Vec<Vec<i64>> = Vec::new();
+    for &target in &targets {
+        let mut block = vec![target];
+
+        while let Some(previous_block) = blocks.last() {
+            if median(previous_block) <= median(&block) {
+                break;
+            }
+
+            let mut merged = blocks.pop().unwrap();
+            merged.extend(block);
+            merged.sort_unstable();
+            block = merged;
+        }
+
+        blocks.push(block);
+    }
+
+    let fitted = blocks.iter().flat_map(|block| {
+        let value = median(block).clamp(lower_limit, upper_limit);
+        std::iter::repeat_n(value, block.len())
+    });
+
+    let mut new_starts = Vec::with_capacity(order.len());
+    let mut cost = 0;
+    for ((value, target), offset) in fitted.zip(&targets).zip(&offsets) {
+        cost += (value - target).abs();
+        new_starts.push(value + offset);
+    }
+
+    (new_starts, cost)
+}
+
+fn median(sorted_values: &[i64]) -> i64 {
+    sorted_values[(sorted_values.len() - 1) / 2]
+}
+
+mod tests {
+    #[test]
+    fn reassign_groups() {
+        use super::{ReassignError, Reassignment, SectionScheduler, MAX_NUM_RANGES};
+        use crate::RangeGroup;
+
+        let scheduler = SectionScheduler::new(1..=9);
+
+        let already_disjoint = RangeGroup {
+            ranges: vec![2..=4, 6..=8],
+        };
+        assert_eq!(
+            scheduler.reassign(&already_disjoint),
+            Ok(Reassignment {
+                ranges: vec![2..=4, 6..=8],
+                total_shift: 0,
+            })
+        );
+
+        // moving the short range in front of the long one is cheaper
+        let short_inside_long = RangeGroup {
+            ranges: vec![1..=5, 2..=2],
+        };
+        assert_eq!(
+            scheduler.reassign(&short_inside_long),
+            Ok(Reassignment {
+                ranges: vec![2..=6, 1..=1],
+                total_shift: 2,
+            })
+        );
+
+        let pushed_against_limit = RangeGroup {
+            ranges: vec![7..=9, 8..=9],
+        };
+        assert_eq!(
+            scheduler.reassign(&pushed_against_limit),
+            Ok(Reassignment {
+                ranges: vec![5..=7, 8..=9],
+                total_shift: 2,
+            })
+        );
+
+        let too_long = RangeGroup {
+            ranges: vec![1..=5, 3..=7],
+        };
+        assert_eq!(
+            scheduler.reassign(&too_long),
+            Err(ReassignError::Infeasible {
+                required_sections: 10,
+                available_sections: 9,
+            })
+        );
+
+        let too_many = RangeGroup {
+            ranges: vec![1..=1; MAX_NUM_RANGES + 1],
+        };
+        assert_eq!(
+            scheduler.reassign(&too_many),
+            Err(ReassignError::TooManyRanges {
+                num_ranges: MAX_NUM_RANGES + 1
+            })
+        );
+    }
+}
@@ -0,0 +1,106 @@
+use std::time::{Duration, Instant};
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::crane::{Crane, CrateMover9000, CrateMover9001, CrateStack};
+use crate::input::{Move, Stack};
+use crate::treap_stack::TreapStack;
+
+pub struct Workload {
+    stacks: Vec<Vec<char>>,
+    moves: Vec<Move>,
+}
+
+// The crates are spread randomly over the stacks and every move takes a uniformly
+// random amount of the crates of its source stack. The heights of the stacks do not
+// depend on the crane, so the moves are valid for all of them.
+pub fn random_workload(
+    num_crates: usize,
+    num_stacks: usize,
+    num_moves: usize,
+    seed: u64,
+) -> Workload {
+    assert!(num_stacks >= 2, "Moves need at least two stacks");
+
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let mut stacks = vec![Vec::new(); num_stacks];
+    for _ in 0..num_crates {
+        let label = rng.gen_range(b'A'..=b'Z') as char;
+        stacks[rng.gen_range(0..num_stacks)].push(label);
+    }
+
+    let mut heights: Vec<_> = stacks.iter().map(Vec::len).collect();
+    let mut moves = Vec::with_capacity(num_moves);
+    for _ in 0..num_moves {
+        let index_from = rng.gen_range(0..num_stacks);
+        let index_to = (index_from + rng.gen_range(1..num_stacks)) % num_stacks;
+        let amount = rng.gen_range(0..=heights[index_from]);
+
+        heights[index_from] -= amount;
+        heights[index_to] += amount;
+        moves.push(Move {
+            index_from,
+            index_to,
+            amount,
+        });
+    }
+
+    Workload { stacks, moves }
+}
+
+pub fn run(workload: &Workload) {
+    println!(
+        "{} crates on {} stacks, {} moves",
+        workload.stacks.iter().map(Vec::len).sum::<usize>(),
+        workload.stacks.len(),
+        workload.moves.len()
+    );
+
+    compare("CrateMover 9000", &CrateMover9000, workload);
+    compare("CrateMover 9001", &CrateMover9001, workload);
+}
+
+fn compare<C: Crane>(crane_name: &str, crane: &C, workload: &Workload) {
+    let mut vec_stacks: Vec<_> = workload
+        .stacks
+        .iter()
+        .map(|crates| Stack {
+            elements: crates.clone(),
+        })
+        .collect();
+    let vec_duration = time_moves(crane, &mut vec_stacks, &workload.moves);
+
+    let mut treap_stacks: Vec<_> = workload
+        .stacks
+        .iter()
+        .map(|crates| crates.iter().copied().collect::<TreapStack<_>>())
+        .collect();
+    let treap_duration = time_moves(crane, &mut treap_stacks, &workload.moves);
+
+    for (vec_stack, treap_stack) in vec_stacks.iter().zip(&treap_stacks) {
+        assert_eq!(
+            vec_stack.elements,
+            treap_stack.to_vec(),
+            "Both stack representations should end with the same crates"
+        );
+    }
+
+    let top_crates: String = treap_stacks
+        .iter()
+        .filter_map(|stack| stack.last())
+        .collect();
+
+    println!(
+        "{crane_name}: Vec {vec_duration:.3?}, treap {treap_duration:.3?}, top crates {top_crates}"
+    );
+}
+
+fn time_moves<C: Crane, S: CrateStack>(crane: &C, stacks: &mut [S], moves: &[Move]) -> Duration {
+    let start = Instant::now();
+    crane
+        .apply_moves(stacks, moves)
+        .expect("Generated moves should be valid");
+    start.elapsed()
+}
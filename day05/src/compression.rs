@@ -0,0 +1,125 @@
+use crate::crane::{Crane, MoveError};
+use crate::input::{Move, Stack};
+
+// Rewrites the move list into a shorter one with the same final stacks. Moves
+// without an effect are dropped, and the last two remaining moves are repeatedly
+// cancelled or merged into one while that does not change the stacks after them.
+// Every rewrite is checked by simulating it on the actual stacks, so it is only
+// applied where it is valid for the given crane and starting stacks.
+pub fn compress<C: Crane>(
+    stacks: &[Stack],
+    moves: &[Move],
+    crane: &C,
+) -> Result<Vec<Move>, MoveError> {
+    let mut compressed_moves: Vec<Move> = Vec::new();
+    // the stacks before each of the compressed moves
+    let mut stacks_before: Vec<Vec<Stack>> = Vec::new();
+    let mut current_stacks = stacks.to_vec();
+
+    for (move_idx, move_) in moves.iter().enumerate() {
+        let mut next_stacks = current_stacks.clone();
+        crane
+            .apply_move(&mut next_stacks, move_)
+            .map_err(|reason| MoveError {
+                move_number: move_idx + 1,
+                reason,
+            })?;
+
+        if next_stacks == current_stacks {
+            continue;
+        }
+
+        compressed_moves.push(move_.clone());
+        stacks_before.push(current_stacks);
+        current_stacks = next_stacks;
+
+        while compressed_moves.len() >= 2 {
+            let num_compressed = compressed_moves.len();
+            let previous = &compressed_moves[num_compressed - 2];
+            let last = &compressed_moves[num_compressed - 1];
+            let base_stacks = &stacks_before[num_compressed - 2];
+
+            if *base_stacks == current_stacks {
+                compressed_moves.truncate(num_compressed - 2);
+                stacks_before.truncate(num_compressed - 2);
+                continue;
+            }
+
+            let merged = (previous.index_from == last.index_from
+                && previous.index_to == last.index_to)
+                .then(|| Move {
+                    index_from: last.index_from,
+                    index_to: last.index_to,
+                    amount: previous.amount + last.amount,
+                });
+
+            let Some(merged) = merged else {
+                break;
+            };
+
+            let mut merged_stacks = base_stacks.clone();
+            if crane.apply_move(&mut merged_stacks, &merged).is_err()
+                || merged_stacks != current_stacks
+            {
+                break;
+            }
+
+            compressed_moves.truncate(num_compressed - 2);
+            compressed_moves.push(merged);
+            stacks_before.truncate(num_compressed - 1);
+        }
+    }
+
+    let mut final_stacks = stacks.to_vec();
+    crane
+        .apply_moves(&mut final_stacks, &compressed_moves)
+        .expect("Compressed moves were all simulated before");
+    assert_eq!(
+        final_stacks, current_stacks,
+        "Compressed moves should lead to the same stacks"
+    );
+
+    Ok(compressed_moves)
+}
+
+mod tests {
+    #[test]
+    fn compress_redundant_moves() {
+        use super::compress;
+        use crate::crane::{CrateMover9000, CrateMover9001};
+        use crate::input::{Move, Stack};
+
+        let labels = |s: &str| s.chars().map(String::from).collect::<Vec<_>>();
+        let stacks = vec![
+            Stack {
+                elements: labels("ABCD"),
+            },
+            Stack {
+                elements: labels("E"),
+            },
+            Stack { elements: vec![] },
+        ];
+        let move_ = |amount, from: usize, to: usize| Move {
+            index_from: from - 1,
+            index_to: to - 1,
+            amount,
+        };
+
+        let moves = [
+            move_(1, 1, 2),
+            move_(2, 1, 2),
+            move_(0, 2, 3),
+            move_(2, 2, 3),
+            move_(2, 3, 2),
+            move_(1, 2, 2),
+        ];
+
+        // one at a time, consecutive moves between the same stacks can always be merged
+        let compressed_moves = compress(&stacks, &moves, &CrateMover9000).unwrap();
+        assert_eq!(compressed_moves, [move_(3, 1, 2)]);
+
+        // in bulk, they can not, but the moves back and forth still cancel out
+        let compressed_moves = compress(&stacks, &moves, &CrateMover9001).unwrap();
+        assert_eq!(compressed_moves, [move_(1, 1, 2), move_(2, 1, 2)]);
+    }
+}
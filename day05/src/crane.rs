@@ -0,0 +1,313 @@
+use std::fmt::Display;
+
+use crate::input::{Move, Stack};
+
+// the operations the cranes need, the crates are ordered from the bottom to the top
+pub trait CrateStack: Sized {
+    fn num_crates(&self) -> usize;
+
+    // removes the topmost crates and returns them as a stack of their own
+    fn split_off_top(&mut self, amount: usize) -> Self;
+
+    fn put_on_top(&mut self, crates: Self);
+
+    fn reverse(&mut self);
+}
+
+impl<T> CrateStack for Stack<T> {
+    fn num_crates(&self) -> usize {
+        self.len()
+    }
+
+    fn split_off_top(&mut self, amount: usize) -> Self {
+        let num_remaining = self.len() - amount;
+
+        Stack {
+            elements: self.split_off(num_remaining),
+        }
+    }
+
+    fn put_on_top(&mut self, mut crates: Self) {
+        self.append(&mut crates);
+    }
+
+    fn reverse(&mut self) {
+        self.elements.reverse();
+    }
+}
+
+pub trait Crane {
+    // leaves the stacks untouched if the move is not possible
+    fn apply_move<S: CrateStack>(
+        &self,
+        stacks: &mut [S],
+        move_: &Move,
+    ) -> Result<(), MoveErrorReason>;
+
+    fn apply_moves<S: CrateStack>(
+        &self,
+        stacks: &mut [S],
+        moves: &[Move],
+    ) -> Result<(), MoveError> {
+        for (move_idx, move_) in moves.iter().enumerate() {
+            self.apply_move(stacks, move_).map_err(|reason| MoveError {
+                move_number: move_idx + 1,
+                reason,
+            })?;
+        }
+
+        Ok(())
+    }
+}
+
+// moves one crate at a time
+pub struct CrateMover9000;
+
+impl Crane for CrateMover9000 {
+    fn apply_move<S: CrateStack>(
+        &self,
+        stacks: &mut [S],
+        move_: &Move,
+    ) -> Result<(), MoveErrorReason> {
+        validate(stacks, move_)?;
+        move_in_lifts(stacks, move_, 1);
+
+        Ok(())
+    }
+}
+
+// moves all crates of a move at once
+pub struct CrateMover9001;
+
+impl Crane for CrateMover9001 {
+    fn apply_move<S: CrateStack>(
+        &self,
+        stacks: &mut [S],
+        move_: &Move,
+    ) -> Result<(), MoveErrorReason> {
+        validate(stacks, move_)?;
+        move_in_lifts(stacks, move_, move_.amount);
+
+        Ok(())
+    }
+}
+
+// moves up to lift_capacity crates at once, so it generalizes both of the above
+pub struct CapacityLimitedCrane {
+    lift_capacity: usize,
+}
+
+impl CapacityLimitedCrane {
+    pub fn new(lift_capacity: usize) -> Self {
+        assert!(lift_capacity > 0, "A crane has to lift at least one crate");
+        CapacityLimitedCrane { lift_capacity }
+    }
+}
+
+impl Crane for CapacityLimitedCrane {
+    fn apply_move<S: CrateStack>(
+        &self,
+        stacks: &mut [S],
+        move_: &Move,
+    ) -> Result<(), MoveErrorReason> {
+        validate(stacks, move_)?;
+        move_in_lifts(stacks, move_, self.lift_capacity);
+
+        Ok(())
+    }
+}
+
+fn validate<S: CrateStack>(stacks: &[S], move_: &Move) -> Result<(), MoveErrorReason> {
+    for stack_index in [move_.index_from, move_.index_to] {
+        if stack_index >= stacks.len() {
+            return Err(MoveErrorReason::StackOutOfRange {
+                stack_number: stack_index + 1,
+                num_stacks: stacks.len(),
+            });
+        }
+    }
+
+    let available = stacks[move_.index_from].num_crates();
+    if move_.amount > available {
+        return Err(MoveErrorReason::NotEnoughCrates {
+            stack_number: move_.index_from + 1,
+            requested: move_.amount,
+            available,
+        });
+    }
+
+    Ok(())
+}
+
+// Each lift takes the topmost crates of the source stack and keeps their order, so
+// the moved crates end up as lift sized chunks in reverse order.
+fn move_in_lifts<S: CrateStack>(stacks: &mut [S], move_: &Move, lift_capacity: usize) {
+    if move_.index_from == move_.index_to {
+        return;
+    }
+
+    let mut moved_crates = stacks[move_.index_from].split_off_top(move_.amount);
+
+    // a single reversal instead of one lift per crate
+    if lift_capacity == 1 {
+        moved_crates.reverse();
+        stacks[move_.index_to].put_on_top(moved_crates);
+        return;
+    }
+
+    while moved_crates.num_crates() > 0 {
+        let lift_size = moved_crates.num_crates().min(lift_capacity);
+        let lifted_crates = moved_crates.split_off_top(lift_size);
+        stacks[move_.index_to].put_on_top(lifted_crates);
+    }
+}
+
+// stacks are counted 1-based like in the input
+#[derive(Debug, PartialEq, Eq)]
+pub enum MoveErrorReason {
+    StackOutOfRange {
+        stack_number: usize,
+        num_stacks: usize,
+    },
+    NotEnoughCrates {
+        stack_number: usize,
+        requested: usize,
+        available: usize,
+    },
+}
+
+impl Display for MoveErrorReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use MoveErrorReason::*;
+
+        match self {
+            StackOutOfRange {
+                stack_number,
+                num_stacks,
+            } => write!(f, "stack {stack_number} does not exist, there are only {num_stacks} stacks"),
+            NotEnoughCrates {
+                stack_number,
+                requested,
+                available,
+            } => write!(
+                f,
+                "cannot take {requested} crates from stack {stack_number}, it only holds {available}"
+            ),
+        }
+    }
+}
+
+// move numbers are 1-based like the lines of the move list
+#[derive(Debug, PartialEq, Eq)]
+pub struct MoveError {
+    pub move_number: usize,
+    pub reason: MoveErrorReason,
+}
+
+impl Display for MoveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "invalid move number {}: {}",
+            self.move_number, self.reason
+        )
+    }
+}
+
+mod tests {
+    #[test]
+    fn invalid_moves_leave_stacks_untouched() {
+        use super::{Crane, CrateMover9001, MoveError, MoveErrorReason};
+        use crate::input::{Move, Stack};
+
+        let stacks = vec![
+            Stack {
+                elements: vec!['A', 'B'],
+            },
+            Stack { elements: vec![] },
+        ];
+
+        let valid_move = Move {
+            index_from: 0,
+            index_to: 1,
+            amount: 1,
+        };
+        let too_many_crates = Move {
+            index_from: 0,
+            index_to: 1,
+            amount: 2,
+        };
+        let missing_stack = Move {
+            index_from: 0,
+            index_to: 2,
+            amount: 1,
+        };
+
+        let mut moved_stacks = stacks.clone();
+        let result = CrateMover9001.apply_moves(&mut moved_stacks, &[valid_move, too_many_crates]);
+        assert_eq!(
+            result,
+            Err(MoveError {
+                move_number: 2,
+                reason: MoveErrorReason::NotEnoughCrates {
+                    stack_number: 1,
+                    requested: 2,
+                    available: 1
+                }
+            })
+        );
+        assert_eq!(moved_stacks[0].elements, ['A']);
+
+        let mut moved_stacks = stacks.clone();
+        let result = CrateMover9001.apply_moves(&mut moved_stacks, &[missing_stack]);
+        assert_eq!(
+            result,
+            Err(MoveError {
+                move_number: 1,
+                reason: MoveErrorReason::StackOutOfRange {
+                    stack_number: 3,
+                    num_stacks: 2
+                }
+            })
+        );
+        assert_eq!(moved_stacks, stacks);
+    }
+
+    #[test]
+    fn lift_capacity() {
+        use super::{CapacityLimitedCrane, Crane, CrateMover9000, CrateMover9001};
+        use crate::input::{Move, Stack};
+
+        let stacks = vec![
+            Stack {
+                elements: vec!['A', 'B', 'C', 'D', 'E'],
+            },
+            Stack { elements: vec![] },
+        ];
+        let move_ = Move {
+            index_from: 0,
+            index_to: 1,
+            amount: 5,
+        };
+
+        let apply = |crane: &dyn Fn(&mut [Stack<char>], &Move)| {
+            let mut moved_stacks = stacks.clone();
+            crane(&mut moved_stacks, &move_);
+            moved_stacks[1].elements.iter().collect::<String>()
+        };
+
+        let capacity_two = CapacityLimitedCrane::new(2);
+        assert_eq!(
+            apply(&|s, m| capacity_two.apply_move(s, m).unwrap()),
+            "DEBCA"
+        );
+        assert_eq!(
+            apply(&|s, m| CrateMover9000.apply_move(s, m).unwrap()),
+            "EDCBA"
+        );
+        assert_eq!(
+            apply(&|s, m| CrateMover9001.apply_move(s, m).unwrap()),
+            "ABCDE"
+        );
+    }
+}
@@ -0,0 +1,130 @@
+use std::fmt::Display;
+
+use crate::input::{Crate, Stack};
+
+// 1-based like in the drawing, the height counts from the bottom of the stack
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    pub stack_number: usize,
+    pub height: usize,
+}
+
+// a crate that is only in the old or only in the new stacks has no position there
+#[derive(Debug, PartialEq, Eq)]
+pub struct CrateChange {
+    pub crate_: Crate,
+    pub old_position: Option<Position>,
+    pub new_position: Option<Position>,
+}
+
+impl Display for CrateChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let format_position = |position: Option<Position>| match position {
+            Some(Position {
+                stack_number,
+                height,
+            }) => format!("stack {stack_number} height {height}"),
+            None => String::from("nowhere"),
+        };
+
+        write!(
+            f,
+            "[{}] {} -> {}",
+            self.crate_,
+            format_position(self.old_position),
+            format_position(self.new_position)
+        )
+    }
+}
+
+// Crates below the lowest change of their stack count as untouched. As crates are
+// only known by their labels, the changed crates are paired up by label, starting
+// at the top of the old stacks.
+pub fn diff(old_stacks: &[Stack], new_stacks: &[Stack]) -> Vec<CrateChange> {
+    let num_stacks = old_stacks.len().max(new_stacks.len());
+    let empty_stack = Stack { elements: vec![] };
+
+    let mut removed = Vec::new();
+    let mut added = Vec::new();
+
+    for stack_idx in 0..num_stacks {
+        let old_stack = old_stacks.get(stack_idx).unwrap_or(&empty_stack);
+        let new_stack = new_stacks.get(stack_idx).unwrap_or(&empty_stack);
+
+        let num_untouched = old_stack
+            .iter()
+            .zip(new_stack.iter())
+            .take_while(|(old_crate, new_crate)| old_crate == new_crate)
+            .count();
+
+        let positions = |stack: &Stack| {
+            (num_untouched..stack.len())
+                .map(|level| {
+                    let position = Position {
+                        stack_number: stack_idx + 1,
+                        height: level + 1,
+                    };
+                    (stack[level].clone(), position)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        removed.extend(positions(old_stack).into_iter().rev());
+        added.extend(positions(new_stack));
+    }
+
+    let mut changes = Vec::new();
+
+    for (crate_, old_position) in removed {
+        let new_position = added
+            .iter()
+            .position(|(added_crate, _)| *added_crate == crate_)
+            .map(|added_idx| added.remove(added_idx).1);
+
+        changes.push(CrateChange {
+            crate_,
+            old_position: Some(old_position),
+            new_position,
+        });
+    }
+
+    changes.extend(added.into_iter().map(|(crate_, new_position)| CrateChange {
+        crate_,
+        old_position: None,
+        new_position: Some(new_position),
+    }));
+
+    changes
+}
+
+mod tests {
+    #[test]
+    fn diff_moved_crates() {
+        use super::{diff, Position};
+        use crate::input::Stack;
+
+        let labels = |s: &str| Stack {
+            elements: s.chars().map(String::from).collect(),
+        };
+
+        let old_stacks = [labels("ZN"), labels("MCD"), labels("P")];
+        let new_stacks = [labels("C"), labels("M"), labels("PDNZ")];
+
+        let changes: Vec<_> = diff(&old_stacks, &new_stacks)
+            .into_iter()
+            .map(|change| {
+                let stack_number = |position: Option<Position>| position.map(|p| p.stack_number);
+                (
+                    change.crate_,
+                    stack_number(change.old_position),
+                    stack_number(change.new_position),
+                )
+            })
+            .collect();
+
+        let expected = [("N", 1, 3), ("Z", 1, 3), ("D", 2, 3), ("C", 2, 1)]
+            .map(|(crate_, old, new)| (String::from(crate_), Some(old), Some(new)));
+
+        assert_eq!(changes, expected);
+    }
+}
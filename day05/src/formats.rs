@@ -0,0 +1,313 @@
+use std::fmt::Write;
+use std::str::FromStr;
+
+use serde::{de, Deserialize, Deserializer, Serialize};
+
+use crate::input::{self, Crate, Move, ParseError, Stack};
+use crate::rendering;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    // the drawing of the puzzle input
+    Drawing,
+    Json,
+    // one line per stack like "1: ZN", then the moves like in the puzzle input
+    Compact,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "drawing" => Ok(Format::Drawing),
+            "json" => Ok(Format::Json),
+            "compact" => Ok(Format::Compact),
+            _ => Err(format!(
+                "Unknown format {s}, expected drawing, json or compact"
+            )),
+        }
+    }
+}
+
+impl Format {
+    pub fn import(&self, input: &str) -> Result<(Vec<Stack>, Vec<Move>), ParseError> {
+        match self {
+            Format::Drawing => input::parse(input),
+            Format::Json => from_json(input),
+            Format::Compact => from_compact(input),
+        }
+    }
+
+    pub fn export(&self, stacks: &[Stack], moves: &[Move]) -> String {
+        match self {
+            Format::Drawing => rendering::render_puzzle(stacks, moves),
+            Format::Json => to_json(stacks, moves),
+            Format::Compact => to_compact(stacks, moves),
+        }
+    }
+}
+
+// stacks are listed bottom first and counted 1-based like in the drawing
+#[derive(Serialize, Deserialize)]
+struct JsonPuzzle {
+    stacks: Vec<Vec<JsonCrate>>,
+    moves: Vec<JsonMove>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonMove {
+    amount: usize,
+    from: JsonStackNumber,
+    to: JsonStackNumber,
+}
+
+// The values are checked while deserializing, so that the errors point at them.
+// Labels are the same as in the drawing, so every export can be imported again.
+#[derive(Serialize)]
+#[serde(transparent)]
+struct JsonCrate(Crate);
+
+impl<'de> Deserialize<'de> for JsonCrate {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let label = Crate::deserialize(deserializer)?;
+
+        if label.is_empty() || !label.chars().all(char::is_alphanumeric) {
+            return Err(de::Error::invalid_value(
+                de::Unexpected::Str(&label),
+                &"an alphanumeric crate label",
+            ));
+        }
+
+        Ok(JsonCrate(label))
+    }
+}
+
+#[derive(Serialize)]
+#[serde(transparent)]
+struct JsonStackNumber(usize);
+
+impl<'de> Deserialize<'de> for JsonStackNumber {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let stack_number = usize::deserialize(deserializer)?;
+
+        if stack_number == 0 {
+            return Err(de::Error::invalid_value(
+                de::Unexpected::Unsigned(0),
+                &"a stack number, they start at 1",
+            ));
+        }
+
+        Ok(JsonStackNumber(stack_number))
+    }
+}
+
+pub fn to_json(stacks: &[Stack], moves: &[Move]) -> String {
+    let json_puzzle = JsonPuzzle {
+        stacks: stacks
+            .iter()
+            .map(|stack| stack.iter().cloned().map(JsonCrate).collect())
+            .collect(),
+        moves: moves
+            .iter()
+            .map(|move_| JsonMove {
+                amount: move_.amount,
+                from: JsonStackNumber(move_.index_from + 1),
+                to: JsonStackNumber(move_.index_to + 1),
+            })
+            .collect(),
+    };
+
+    let mut json = serde_json::to_string_pretty(&json_puzzle).expect("Puzzle should serialize");
+    json.push('\n');
+    json
+}
+
+pub fn from_json(input: &str) -> Result<(Vec<Stack>, Vec<Move>), ParseError> {
+    let json_puzzle: JsonPuzzle = serde_json::from_str(input).map_err(|error| ParseError {
+        line: error.line(),
+        column: error.column(),
+        message: error.to_string(),
+    })?;
+
+    let stacks = json_puzzle
+        .stacks
+        .into_iter()
+        .map(|crates| Stack {
+            elements: crates.into_iter().map(|JsonCrate(label)| label).collect(),
+        })
+        .collect();
+
+    let moves = json_puzzle
+        .moves
+        .into_iter()
+        .map(|json_move| Move {
+            index_from: json_move.from.0 - 1,
+            index_to: json_move.to.0 - 1,
+            amount: json_move.amount,
+        })
+        .collect();
+
+    Ok((stacks, moves))
+}
+
+// Labels are written next to each other as long as all of them are single
+// characters, otherwise they keep their brackets from the drawing.
+pub fn to_compact(stacks: &[Stack], moves: &[Move]) -> String {
+    let mut s = String::new();
+
+    let all_single_characters = stacks
+        .iter()
+        .flat_map(|stack| stack.iter())
+        .all(|crate_| crate_.chars().count() == 1);
+
+    for (stack_idx, stack) in stacks.iter().enumerate() {
+        let crates: Vec<_> = if all_single_characters {
+            stack.iter().map(String::clone).collect()
+        } else {
+            stack.iter().map(|crate_| format!("[{crate_}]")).collect()
+        };
+
+        let separator = if all_single_characters { "" } else { " " };
+        let crates = crates.join(separator);
+
+        // stacks are counted 1-based
+        if crates.is_empty() {
+            writeln!(s, "{}:", stack_idx + 1).unwrap();
+        } else {
+            writeln!(s, "{}: {crates}", stack_idx + 1).unwrap();
+        }
+    }
+
+    writeln!(s).unwrap();
+    for move_ in moves {
+        writeln!(s, "{move_}").unwrap();
+    }
+
+    s
+}
+
+pub fn from_compact(input: &str) -> Result<(Vec<Stack>, Vec<Move>), ParseError> {
+    let lines: Vec<_> = input.lines().map(str::trim_end).collect();
+    let separator_idx = lines
+        .iter()
+        .position(|line| line.is_empty())
+        .unwrap_or(lines.len());
+
+    let stacks = lines[..separator_idx]
+        .iter()
+        .enumerate()
+        .map(|(line_idx, line)| compact_stack_line(line, line_idx))
+        .collect::<Result<_, _>>()?;
+
+    let moves = match lines.get(separator_idx + 1..) {
+        Some(move_lines) => input::parse_move_lines(move_lines, separator_idx + 1)?,
+        None => Vec::new(),
+    };
+
+    Ok((stacks, moves))
+}
+
+fn compact_stack_line(line: &str, line_idx: usize) -> Result<Stack, ParseError> {
+    // stacks are counted 1-based
+    let expected_prefix = format!("{}:", line_idx + 1);
+    let Some(crates) = line.strip_prefix(&expected_prefix) else {
+        return Err(ParseError::new(
+            line_idx,
+            0,
+            format!("expected the line to start with \"{expected_prefix}\""),
+        ));
+    };
+
+    let crates_column_idx = expected_prefix.len() + crates.len() - crates.trim_start().len();
+    let crates = crates.trim_start();
+
+    if !crates.starts_with('[') {
+        return match crates.chars().position(|c| !c.is_alphanumeric()) {
+            Some(column_offset) => Err(ParseError::new(
+                line_idx,
+                crates_column_idx + column_offset,
+                "expected alphanumeric crate labels",
+            )),
+            None => Ok(Stack {
+                elements: crates.chars().map(String::from).collect(),
+            }),
+        };
+    }
+
+    let mut elements = Vec::new();
+    let mut column_offset = 0;
+
+    for cell in crates.split(' ') {
+        let label = cell
+            .strip_prefix('[')
+            .and_then(|label| label.strip_suffix(']'))
+            .filter(|label| !label.is_empty() && label.chars().all(char::is_alphanumeric));
+
+        match label {
+            Some(label) => elements.push(label.to_owned()),
+            None if cell.is_empty() => {}
+            None => {
+                return Err(ParseError::new(
+                    line_idx,
+                    crates_column_idx + column_offset,
+                    "expected a crate label in brackets like [AB]",
+                ))
+            }
+        }
+
+        // the cell and the space after it
+        column_offset += cell.chars().count() + 1;
+    }
+
+    Ok(Stack { elements })
+}
+
+mod tests {
+    #[test]
+    fn convert_between_formats() {
+        use super::Format;
+
+        let drawing = "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 \n\nmove 1 from 2 to 1\n";
+        let compact = "1: ZN\n2: MCD\n3: P\n\nmove 1 from 2 to 1\n";
+
+        let (stacks, moves) = Format::Drawing.import(drawing).unwrap();
+        assert_eq!(Format::Compact.export(&stacks, &moves), compact);
+        assert_eq!(
+            Format::Compact.import(compact).unwrap(),
+            (stacks.clone(), moves.clone())
+        );
+
+        let json = Format::Json.export(&stacks, &moves);
+        assert_eq!(Format::Json.import(&json).unwrap(), (stacks, moves));
+
+        for label in ["", "A B", "[A]"] {
+            let json = format!(r#"{{"stacks": [["Z", "{label}"]], "moves": []}}"#);
+            assert!(Format::Json.import(&json).is_err());
+        }
+
+        // the errors point at the end of the invalid values
+        let json = "{\"stacks\": [[\"A\", \"\"]],\n \"moves\": []}";
+        let error = Format::Json.import(json).unwrap_err();
+        assert_eq!((error.line, error.column), (1, 21));
+
+        let json = "{\"stacks\": [],\n \"moves\": [{\"amount\": 1, \"from\": 0, \"to\": 1}]}";
+        let error = Format::Json.import(json).unwrap_err();
+        assert_eq!((error.line, error.column), (2, 34));
+    }
+
+    #[test]
+    fn compact_multi_character_labels() {
+        use super::Format;
+
+        let compact = "1: [AB] [C]\n2:\n3: [D]\n\nmove 2 from 1 to 2\n";
+
+        let (stacks, moves) = Format::Compact.import(compact).unwrap();
+        assert_eq!(stacks[0].elements, ["AB", "C"]);
+        assert!(stacks[1].is_empty());
+        assert_eq!(Format::Compact.export(&stacks, &moves), compact);
+
+        let error = Format::Compact.import("1: [AB] C]\n").unwrap_err();
+        assert_eq!((error.line, error.column), (1, 9));
+    }
+}
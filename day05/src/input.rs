@@ -1,119 +1,318 @@
+use std::fmt::Display;
 use std::ops::{Deref, DerefMut};
 
 use nom::{
-    branch::alt,
     bytes::complete::tag,
-    character::{
-        complete::{alpha1, char, digit1, line_ending},
-        is_alphabetic,
-    },
-    multi::{many1, separated_list1},
-    sequence::{delimited, pair, preceded, terminated, tuple},
+    character::complete::digit1,
+    combinator::{all_consuming, map_res},
+    sequence::{preceded, tuple},
     IResult, Parser,
 };
 
-pub fn parse(input: &str) -> (Vec<Stack>, Vec<Move>) {
-    let (rest, stacks_and_moves) = pair(stacks, moves)(input).expect("Parsing error");
-    assert!(rest.is_empty());
+// line and column are 1-based, the column counts characters
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
 
-    stacks_and_moves
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
 }
 
-fn stacks(input: &str) -> IResult<&str, Vec<Stack>> {
-    let stack_positions = separated_list1(char(' '), stack_position);
-    let stack_matrix = separated_list1(line_ending, stack_positions);
+impl ParseError {
+    pub fn new(line_idx: usize, column_idx: usize, message: impl Into<String>) -> Self {
+        ParseError {
+            line: line_idx + 1,
+            column: column_idx + 1,
+            message: message.into(),
+        }
+    }
+}
 
-    let stacks = stack_matrix.map(|matrix| {
-        let num_stacks = matrix[0].len();
-        let mut stacks = vec![Stack::new(); num_stacks];
+// The drawing and the moves are separated by the first empty line. Line endings
+// may be LF or CRLF, and trailing whitespace is ignored everywhere.
+pub fn parse(input: &str) -> Result<(Vec<Stack>, Vec<Move>), ParseError> {
+    let lines: Vec<_> = input.lines().map(str::trim_end).collect();
 
-        for stack_line in matrix.iter().rev() {
-            for (i, stack_pos) in stack_line.iter().enumerate() {
-                if let StackPosition::Crate(c) = stack_pos {
-                    stacks[i].push(*c);
-                }
-            }
-        }
+    let Some(separator_idx) = lines.iter().position(|line| line.is_empty()) else {
+        return Err(ParseError::new(
+            lines.len(),
+            0,
+            "expected an empty line between the drawing and the moves",
+        ));
+    };
 
-        stacks
-    });
+    let stacks = parse_drawing_lines(&lines[..separator_idx], 0)?;
+    let moves = parse_move_lines(&lines[separator_idx + 1..], separator_idx + 1)?;
 
-    let terminated_stacks = terminated(stacks, line_ending);
+    Ok((stacks, moves))
+}
 
-    let padded_number = delimited(char(' '), digit1, char(' '));
+// one move per line, empty lines are skipped
+pub fn parse_move_lines(lines: &[&str], first_line_idx: usize) -> Result<Vec<Move>, ParseError> {
+    lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| !line.trim_end().is_empty())
+        .map(|(line_offset, line)| parse_move_line(line.trim_end(), first_line_idx + line_offset))
+        .collect()
+}
 
-    let stack_numbers = separated_list1(char(' '), padded_number);
-    let stack_number_line = terminated(stack_numbers, line_ending);
+#[allow(unused)]
+pub fn parse_stacks(drawing_input: &str) -> Result<Vec<Stack>, ParseError> {
+    let lines: Vec<_> = drawing_input.lines().map(str::trim_end).collect();
 
-    let stacks_with_numbers_dropped = terminated(terminated_stacks, stack_number_line);
+    // allow empty lines around the drawing, but keep the line numbers intact
+    let first_line_idx = lines.iter().position(|line| !line.is_empty()).unwrap_or(0);
+    let end_line_idx = lines
+        .iter()
+        .rposition(|line| !line.is_empty())
+        .map_or(0, |idx| idx + 1);
 
-    terminated(stacks_with_numbers_dropped, line_ending)(input)
+    parse_drawing_lines(&lines[first_line_idx..end_line_idx], first_line_idx)
 }
 
-fn stack_position(input: &str) -> IResult<&str, StackPosition> {
-    let empty = tag("   ");
-    let crate_ = delimited(char('['), alpha1, char(']'));
+// a token of the drawing, the columns are character indices within its line
+struct Cell<'a> {
+    text: &'a str,
+    first_column_idx: usize,
+    last_column_idx: usize,
+}
 
-    alt((empty, crate_)).map(|s: &str| s.into()).parse(input)
+impl Cell<'_> {
+    fn overlaps(&self, other: &Cell) -> bool {
+        self.first_column_idx <= other.last_column_idx
+            && other.first_column_idx <= self.last_column_idx
+    }
 }
 
-fn moves(input: &str) -> IResult<&str, Vec<Move>> {
-    let move_line = terminated(move_, line_ending);
+// The stack number line determines where the stacks are, so the crate cells may
+// have any width and there may be any number of stacks. Every crate has to overlap
+// exactly one of the stack numbers.
+fn parse_drawing_lines(lines: &[&str], first_line_idx: usize) -> Result<Vec<Stack>, ParseError> {
+    let Some((&number_line, crate_lines)) = lines.split_last() else {
+        return Err(ParseError::new(
+            first_line_idx,
+            0,
+            "expected a drawing of stacks",
+        ));
+    };
+
+    let number_line_idx = first_line_idx + crate_lines.len();
+    let stack_numbers = stack_number_cells(number_line, number_line_idx)?;
+
+    let mut stacks = vec![Stack::new(); stack_numbers.len()];
+
+    // bottom up, so every crate is put onto the crate below it
+    for (line_offset, &line) in crate_lines.iter().enumerate().rev() {
+        let line_idx = first_line_idx + line_offset;
+        let level = crate_lines.len() - 1 - line_offset;
+
+        let mut filled_stacks = vec![false; stacks.len()];
+
+        for crate_cell in crate_cells(line, line_idx)? {
+            let mut overlapping = stack_numbers
+                .iter()
+                .enumerate()
+                .filter(|(_, number_cell)| number_cell.overlaps(&crate_cell))
+                .map(|(stack_idx, _)| stack_idx);
+
+            let (Some(stack_idx), None) = (overlapping.next(), overlapping.next()) else {
+                return Err(ParseError::new(
+                    line_idx,
+                    crate_cell.first_column_idx,
+                    "expected the crate to be above exactly one stack number",
+                ));
+            };
+
+            if filled_stacks[stack_idx] {
+                return Err(ParseError::new(
+                    line_idx,
+                    crate_cell.first_column_idx,
+                    format!("found a second crate for stack {}", stack_idx + 1),
+                ));
+            }
 
-    many1(move_line)(input)
+            if stacks[stack_idx].len() != level {
+                return Err(ParseError::new(
+                    line_idx,
+                    crate_cell.first_column_idx,
+                    "expected another crate below this crate",
+                ));
+            }
+
+            filled_stacks[stack_idx] = true;
+            stacks[stack_idx].push(crate_cell.text.to_owned());
+        }
+    }
+
+    Ok(stacks)
 }
 
-fn move_(input: &str) -> IResult<&str, Move> {
-    let move_tag = tag::<_, &str, _>("move ");
-    let from_tag = tag(" from ");
-    let to_tag = tag(" to ");
+fn stack_number_cells(line: &str, line_idx: usize) -> Result<Vec<Cell<'_>>, ParseError> {
+    let mut cells = Vec::new();
+    let mut chars = line.char_indices().enumerate().peekable();
 
-    let amount_value = preceded(move_tag, digit1);
-    let from_value = preceded(from_tag, digit1);
-    let to_value = preceded(to_tag, digit1);
+    while let Some((column_idx, (byte_idx, c))) = chars.next() {
+        if c == ' ' {
+            continue;
+        }
 
-    let whole_move = tuple((amount_value, from_value, to_value));
+        if !c.is_ascii_digit() {
+            return Err(ParseError::new(
+                line_idx,
+                column_idx,
+                "expected a stack number or a space",
+            ));
+        }
 
-    whole_move
-        .map(|(amount, from, to)| Move {
-            index_from: from.parse::<usize>().unwrap() - 1, // stacks are counted 1-based in input
-            index_to: to.parse::<usize>().unwrap() - 1,     // stacks are counted 1-based in input
-            amount: amount.parse().unwrap(),
-        })
-        .parse(input)
-}
+        let mut last_column_idx = column_idx;
+        let mut end_byte_idx = byte_idx + 1;
+        while let Some(&(next_column_idx, (next_byte_idx, next_c))) = chars.peek() {
+            if !next_c.is_ascii_digit() {
+                break;
+            }
 
-enum StackPosition {
-    Empty,
-    Crate(char),
-}
+            last_column_idx = next_column_idx;
+            end_byte_idx = next_byte_idx + 1;
+            chars.next();
+        }
 
-impl From<&str> for StackPosition {
-    fn from(s: &str) -> Self {
-        if s == "   " {
-            return StackPosition::Empty;
-        };
+        let text = &line[byte_idx..end_byte_idx];
+        let expected_number = cells.len() + 1;
+
+        // stacks are counted 1-based in input
+        if text.parse() != Ok(expected_number) {
+            return Err(ParseError::new(
+                line_idx,
+                column_idx,
+                format!("expected stack number {expected_number}"),
+            ));
+        }
+
+        cells.push(Cell {
+            text,
+            first_column_idx: column_idx,
+            last_column_idx,
+        });
+    }
 
-        if s.len() != 1 || !s.is_ascii() {
-            panic!("Expected single ASCII character crate names")
+    if cells.is_empty() {
+        return Err(ParseError::new(
+            line_idx,
+            0,
+            "expected a line of stack numbers",
+        ));
+    }
+
+    Ok(cells)
+}
+
+// the text of a crate cell is only its label, without the brackets
+fn crate_cells(line: &str, line_idx: usize) -> Result<Vec<Cell<'_>>, ParseError> {
+    let mut cells = Vec::new();
+    let mut chars = line.char_indices().enumerate();
+
+    while let Some((column_idx, (byte_idx, c))) = chars.next() {
+        match c {
+            ' ' => continue,
+            '[' => {}
+            _ => {
+                return Err(ParseError::new(
+                    line_idx,
+                    column_idx,
+                    "expected '[' or a space",
+                ))
+            }
         }
 
-        let c = s.chars().next().unwrap();
+        let label_start_byte_idx = byte_idx + 1;
+
+        let closing_bracket = chars.by_ref().find(|(_, (_, c))| !c.is_alphanumeric());
+        let Some((last_column_idx, (closing_byte_idx, ']'))) = closing_bracket else {
+            let error_column_idx = closing_bracket.map_or(line.chars().count(), |(idx, _)| idx);
+            return Err(ParseError::new(
+                line_idx,
+                error_column_idx,
+                "expected an alphanumeric crate label followed by ']'",
+            ));
+        };
 
-        if !is_alphabetic(c as u8) {
-            panic!("Expected alphabetic crate names")
+        if closing_byte_idx == label_start_byte_idx {
+            return Err(ParseError::new(
+                line_idx,
+                column_idx,
+                "expected a non-empty crate label",
+            ));
         }
 
-        StackPosition::Crate(c)
+        cells.push(Cell {
+            text: &line[label_start_byte_idx..closing_byte_idx],
+            first_column_idx: column_idx,
+            last_column_idx,
+        });
     }
+
+    Ok(cells)
 }
 
-#[derive(Debug, Clone)]
-pub struct Stack {
-    pub elements: Vec<char>,
+fn parse_move_line(line: &str, line_idx: usize) -> Result<Move, ParseError> {
+    let (_, (amount, from, to)) = all_consuming(move_)(line).map_err(|error| {
+        let rest = match error {
+            nom::Err::Error(error) | nom::Err::Failure(error) => error.input,
+            nom::Err::Incomplete(_) => "",
+        };
+        let column_idx = line[..line.len() - rest.len()].chars().count();
+
+        ParseError::new(
+            line_idx,
+            column_idx,
+            "expected a move like 'move 1 from 2 to 3'",
+        )
+    })?;
+
+    // stacks are counted 1-based in input
+    let index = |stack_number: usize, prefix: &str| {
+        stack_number.checked_sub(1).ok_or_else(|| {
+            let column_idx = line.find(prefix).unwrap() + prefix.len();
+            ParseError::new(line_idx, column_idx, "stack numbers start at 1")
+        })
+    };
+
+    Ok(Move {
+        index_from: index(from, " from ")?,
+        index_to: index(to, " to ")?,
+        amount,
+    })
+}
+
+fn move_(input: &str) -> IResult<&str, (usize, usize, usize)> {
+    let number = || map_res(digit1, str::parse::<usize>);
+
+    let amount_value = preceded(tag("move "), number());
+    let from_value = preceded(tag(" from "), number());
+    let to_value = preceded(tag(" to "), number());
+
+    tuple((amount_value, from_value, to_value)).parse(input)
 }
 
-impl Stack {
+pub type Crate = String;
+
+// the crates are usually just their labels, but they can carry additional data
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Stack<T = Crate> {
+    pub elements: Vec<T>,
+}
+
+impl<T> Stack<T> {
     fn new() -> Self {
         Stack {
             elements: Vec::new(),
@@ -121,23 +320,90 @@ impl Stack {
     }
 }
 
-impl Deref for Stack {
-    type Target = Vec<char>;
+impl<T> Deref for Stack<T> {
+    type Target = Vec<T>;
 
     fn deref(&self) -> &Self::Target {
         &self.elements
     }
 }
 
-impl DerefMut for Stack {
+impl<T> DerefMut for Stack<T> {
     fn deref_mut(&mut self) -> &mut Self::Target {
         &mut self.elements
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Move {
     pub index_from: usize,
     pub index_to: usize,
     pub amount: usize,
 }
+
+impl Display for Move {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // stacks are counted 1-based in input
+        write!(
+            f,
+            "move {} from {} to {}",
+            self.amount,
+            self.index_from + 1,
+            self.index_to + 1
+        )
+    }
+}
+
+mod tests {
+    #[test]
+    fn parse_irregular_drawing() {
+        use super::parse;
+
+        // ten stacks, a multi-letter label, trimmed trailing spaces and CRLF
+        let input = "[A]                                 [AB]\r\n\
+                     [B]             [C]                 [CD]\r\n \
+                     1   2   3   4   5   6   7   8   9   10\r\n\
+                     \r\n\
+                     move 1 from 10 to 2\r\n";
+
+        let (stacks, moves) = parse(input).unwrap();
+
+        assert_eq!(stacks.len(), 10);
+        assert_eq!(stacks[0].elements, ["B", "A"]);
+        assert_eq!(stacks[4].elements, ["C"]);
+        assert_eq!(stacks[9].elements, ["CD", "AB"]);
+        assert_eq!(moves[0].to_string(), "move 1 from 10 to 2");
+    }
+
+    #[test]
+    fn report_first_problem() {
+        use super::{parse, ParseError};
+
+        let error_position = |input: &str| {
+            parse(input)
+                .map(|_| ())
+                .map_err(|ParseError { line, column, .. }| (line, column))
+        };
+
+        assert_eq!(
+            error_position("[A] [B}\n 1   2\n\nmove 1 from 1 to 2\n"),
+            Err((1, 7))
+        );
+        assert_eq!(
+            error_position("[A] [B]\n 1   3\n\nmove 1 from 1 to 2\n"),
+            Err((2, 6))
+        );
+        assert_eq!(
+            error_position("[A]\n    [B]\n 1   2\n\nmove 1 from 1 to 2\n"),
+            Err((1, 1))
+        );
+        assert_eq!(
+            error_position("[A] [B]\n 1   2\n\nmove 1 form 1 to 2\n"),
+            Err((4, 7))
+        );
+        assert_eq!(
+            error_position("[A] [B]\n 1   2\n\nmove 1 from 0 to 2\n"),
+            Err((4, 13))
+        );
+    }
+}
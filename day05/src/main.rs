@@ -1,42 +1,306 @@
-use std::fs;
+use std::io::{BufRead, Read};
+use std::time::Duration;
+use std::{env, fs, io};
 
+mod bench;
+mod compression;
+mod crane;
+mod diff;
+mod formats;
 mod input;
+mod rendering;
+mod search;
+mod session;
+mod trace;
+mod treap_stack;
+
+use crane::{
+    CapacityLimitedCrane, Crane, CrateMover9000, CrateMover9001, CrateStack, MoveErrorReason,
+};
+use input::{Crate, Move};
+use session::Session;
+use trace::TraceOptions;
 
 fn main() {
+    let args: Vec<_> = env::args().skip(1).collect();
+
+    // the benchmark generates its own input
+    if args.first().map(String::as_str) == Some("bench") {
+        bench::run(&parse_bench_args(&args[1..]));
+        return;
+    }
+
+    // usage: convert <from format> <to format>, reads stdin and writes stdout
+    if args.first().map(String::as_str) == Some("convert") {
+        let [from_format, to_format] = [1, 2].map(|arg_idx| {
+            let format_name = args.get(arg_idx).expect("Expected two formats");
+            format_name
+                .parse::<formats::Format>()
+                .unwrap_or_else(|error| panic!("{error}"))
+        });
+
+        let mut input_string = String::new();
+        io::stdin().read_to_string(&mut input_string).unwrap();
+
+        let (stacks, moves) = from_format
+            .import(&input_string)
+            .unwrap_or_else(|error| panic!("{error}"));
+        print!("{}", to_format.export(&stacks, &moves));
+        return;
+    }
+
     let input_string = fs::read_to_string("input/day5.txt").unwrap();
-    let (stacks, moves) = input::parse(&input_string);
+    let (stacks, moves) = input::parse(&input_string).unwrap_or_else(|error| panic!("{error}"));
 
-    let mut one_at_a_time_stacks = stacks.clone();
-    for move_ in &moves {
-        for _ in 0..move_.amount {
-            let crate_ = one_at_a_time_stacks[move_.index_from]
-                .pop()
-                .expect("Trying to move a crate from an empty stack");
-            one_at_a_time_stacks[move_.index_to].push(crate_);
+    match args.first().map(String::as_str) {
+        Some("trace") => {
+            let (crane_choice, options) = parse_trace_args(&args[1..]);
+            let out = &mut io::stdout().lock();
+
+            trace::trace(&stacks, &moves, &crane_choice, &options, out)
+                .unwrap_or_else(|error| panic!("{error}"));
+            return;
+        }
+        Some("search") => {
+            let (target_top_crates, crane_choice, max_states) = parse_search_args(&args[1..]);
+
+            let moves = search::shortest_move_sequence(
+                &stacks,
+                &target_top_crates,
+                &crane_choice,
+                max_states,
+            )
+            .unwrap_or_else(|error| panic!("{error}"));
+
+            print!("{}", rendering::render_puzzle(&stacks, &moves));
+            return;
+        }
+        Some("compress") => {
+            let crane_choice =
+                parse_crane_args(&args[1..], |arg, _| panic!("Unknown compress option {arg}"));
+
+            let compressed_moves = compression::compress(&stacks, &moves, &crane_choice)
+                .unwrap_or_else(|error| panic!("{error}"));
+
+            eprintln!(
+                "Compressed {} moves to {} moves with the same final stacks",
+                moves.len(),
+                compressed_moves.len()
+            );
+
+            print!("{}", rendering::render_puzzle(&stacks, &compressed_moves));
+            return;
+        }
+        Some("diff") => {
+            let crane_choice =
+                parse_crane_args(&args[1..], |arg, _| panic!("Unknown diff option {arg}"));
+
+            let mut final_stacks = stacks.clone();
+            crane_choice
+                .apply_moves(&mut final_stacks, &moves)
+                .unwrap_or_else(|error| panic!("{error}"));
+
+            for change in diff::diff(&stacks, &final_stacks) {
+                println!("{change}");
+            }
+            return;
+        }
+        Some("session") => {
+            let crane_choice =
+                parse_crane_args(&args[1..], |arg, _| panic!("Unknown session option {arg}"));
+
+            run_session(Session::new(&stacks, &moves, &crane_choice));
+            return;
         }
+        _ => {}
     }
 
+    let mut one_at_a_time_stacks = stacks.clone();
+    CrateMover9000
+        .apply_moves(&mut one_at_a_time_stacks, &moves)
+        .unwrap_or_else(|error| panic!("{error}"));
+
     let final_top_crates: String = collect_final_top_crates(&one_at_a_time_stacks);
     println!("Final crates on the top of the stacks (moved one at a time): {final_top_crates}");
 
     let mut bulk_move_stacks = stacks;
-    for move_ in &moves {
-        let stack_from = &mut bulk_move_stacks[move_.index_from];
-        let num_remaining = stack_from.len() - move_.amount;
+    CrateMover9001
+        .apply_moves(&mut bulk_move_stacks, &moves)
+        .unwrap_or_else(|error| panic!("{error}"));
+
+    let final_top_crates: String = collect_final_top_crates(&bulk_move_stacks);
+    println!("Final crates on the top of the stacks (bulk moved): {final_top_crates}");
+}
+
+enum CraneChoice {
+    CrateMover9000,
+    CrateMover9001,
+    CapacityLimited(CapacityLimitedCrane),
+}
 
-        let crates = stack_from[num_remaining..].to_vec();
-        stack_from.truncate(num_remaining);
+impl Crane for CraneChoice {
+    fn apply_move<S: CrateStack>(
+        &self,
+        stacks: &mut [S],
+        move_: &Move,
+    ) -> Result<(), MoveErrorReason> {
+        match self {
+            CraneChoice::CrateMover9000 => CrateMover9000.apply_move(stacks, move_),
+            CraneChoice::CrateMover9001 => CrateMover9001.apply_move(stacks, move_),
+            CraneChoice::CapacityLimited(crane) => crane.apply_move(stacks, move_),
+        }
+    }
+}
+
+// Options shared by all modes: [--bulk | --lift-capacity <crates per lift>]
+// Calls handle_other_arg with any other argument and a way to get its value.
+fn parse_crane_args<F>(args: &[String], mut handle_other_arg: F) -> CraneChoice
+where
+    F: FnMut(&str, &mut dyn FnMut() -> usize),
+{
+    let mut crane_choice = CraneChoice::CrateMover9000;
 
-        bulk_move_stacks[move_.index_to].extend_from_slice(&crates);
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .and_then(|value| value.parse().ok())
+                .unwrap_or_else(|| panic!("Expected a number after {arg}"))
+        };
+
+        match arg.as_str() {
+            "--bulk" => crane_choice = CraneChoice::CrateMover9001,
+            "--lift-capacity" => {
+                crane_choice = CraneChoice::CapacityLimited(CapacityLimitedCrane::new(value()))
+            }
+            _ => handle_other_arg(arg, &mut value),
+        }
     }
 
-    let final_top_crates: String = collect_final_top_crates(&bulk_move_stacks);
-    println!("Final crates on the top of the stacks (bulk moved): {final_top_crates}");
+    crane_choice
+}
+
+// usage: trace <crane options> [--every <moves per frame>] [--delay-ms <milliseconds per frame>]
+fn parse_trace_args(args: &[String]) -> (CraneChoice, TraceOptions) {
+    let mut options = TraceOptions {
+        moves_per_frame: 1,
+        frame_delay: None,
+    };
+
+    let crane_choice = parse_crane_args(args, |arg, value| match arg {
+        "--every" => options.moves_per_frame = value(),
+        "--delay-ms" => options.frame_delay = Some(Duration::from_millis(value() as u64)),
+        _ => panic!("Unknown trace option {arg}"),
+    });
+
+    (crane_choice, options)
+}
+
+// usage: search <target top crates> <crane options> [--max-states <number of states>]
+// Labels longer than one character are given separated by commas, e.g. AB,C,DE
+fn parse_search_args(args: &[String]) -> (Vec<Crate>, CraneChoice, usize) {
+    let target_arg = args.first().expect("Expected target top crates");
+    let target_top_crates = if target_arg.contains(',') {
+        target_arg.split(',').map(String::from).collect()
+    } else {
+        target_arg.chars().map(String::from).collect()
+    };
+    let mut max_states = 1_000_000;
+
+    let crane_choice = parse_crane_args(&args[1..], |arg, value| match arg {
+        "--max-states" => max_states = value(),
+        _ => panic!("Unknown search option {arg}"),
+    });
+
+    (target_top_crates, crane_choice, max_states)
+}
+
+// Reads commands from stdin and prints the stacks after each of them:
+// next | prev | goto <number of applied moves> | who <stack number> <height from bottom>
+fn run_session<C: Crane>(mut session: Session<C>) {
+    println!("{}", rendering::render_drawing(&session.stacks()));
+
+    for line in io::stdin().lock().lines() {
+        let line = line.unwrap();
+        let words: Vec<_> = line.split_whitespace().collect();
+        let numbers: Vec<usize> = words
+            .iter()
+            .skip(1)
+            .filter_map(|w| w.parse().ok())
+            .collect();
+
+        match (words.first().copied(), numbers.as_slice()) {
+            (Some("next" | "redo"), []) => match session.step_forward() {
+                Ok(true) => {}
+                Ok(false) => println!("All moves are applied already"),
+                Err(error) => println!("{error}"),
+            },
+            (Some("prev" | "undo"), []) => {
+                if !session.step_backward() {
+                    println!("No moves are applied yet");
+                }
+            }
+            (Some("goto"), &[num_applied]) if num_applied <= session.num_moves() => {
+                if let Err(error) = session.jump_to(num_applied) {
+                    println!("{error}");
+                }
+            }
+            (Some("who"), &[stack_number, height]) if stack_number > 0 && height > 0 => {
+                match session.crate_at(stack_number - 1, height - 1) {
+                    Some(tracked) => match tracked.last_move_number {
+                        Some(move_number) => {
+                            println!("[{}] was last moved by move {move_number}", tracked.label)
+                        }
+                        None => println!("[{}] was never moved", tracked.label),
+                    },
+                    None => println!("There is no crate at that position"),
+                }
+                continue;
+            }
+            _ => {
+                println!("Unknown command {line}");
+                continue;
+            }
+        }
+
+        println!(
+            "after move {} of {}",
+            session.num_applied(),
+            session.num_moves()
+        );
+        println!("{}", rendering::render_drawing(&session.stacks()));
+    }
+}
+
+// usage: bench [--crates <number>] [--stacks <number>] [--moves <number>] [--seed <number>]
+fn parse_bench_args(args: &[String]) -> bench::Workload {
+    let mut num_crates = 1_000_000;
+    let mut num_stacks = 9;
+    let mut num_moves = 10_000;
+    let mut seed = 0;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let value = args
+            .next()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or_else(|| panic!("Expected a number after {arg}"));
+
+        match arg.as_str() {
+            "--crates" => num_crates = value,
+            "--stacks" => num_stacks = value,
+            "--moves" => num_moves = value,
+            "--seed" => seed = value,
+            _ => panic!("Unknown bench option {arg}"),
+        }
+    }
+
+    bench::random_workload(num_crates, num_stacks, num_moves, seed as u64)
 }
 
 fn collect_final_top_crates(stacks: &[input::Stack]) -> String {
     stacks
         .iter()
-        .map(|stack| stack.last().expect("Empty stack in the end"))
+        .map(|stack| stack.last().expect("Empty stack in the end").as_str())
         .collect()
 }
@@ -0,0 +1,137 @@
+use std::fmt::Write;
+
+use crate::input::{Move, Stack};
+
+// the complete puzzle input, so it can be fed back to the parser
+pub fn render_puzzle(stacks: &[Stack], moves: &[Move]) -> String {
+    let mut s = render_drawing(stacks);
+
+    writeln!(s).unwrap();
+    for move_ in moves {
+        writeln!(s, "{move_}").unwrap();
+    }
+
+    s
+}
+
+// inverse of input::parse_stacks, the lines keep their trailing spaces
+pub fn render_drawing(stacks: &[Stack]) -> String {
+    render_drawing_with(stacks, |crate_| format!("[{crate_}]"))
+}
+
+// All cells are as wide as the widest rendered crate, and the stack numbers are
+// centered below them, so the drawing also works for long labels and many stacks.
+pub fn render_drawing_with<T, F>(stacks: &[Stack<T>], render_crate: F) -> String
+where
+    F: Fn(&T) -> String,
+{
+    let mut s = String::new();
+
+    let rendered_stacks: Vec<Vec<_>> = stacks
+        .iter()
+        .map(|stack| stack.iter().map(&render_crate).collect())
+        .collect();
+
+    let max_crate_width = rendered_stacks
+        .iter()
+        .flatten()
+        .map(|rendered_crate| rendered_crate.chars().count())
+        .max()
+        .unwrap_or(0);
+    let max_number_width = stacks.len().to_string().len();
+    let cell_width = max_crate_width.max(max_number_width).max(3);
+
+    let max_height = stacks.iter().map(|stack| stack.len()).max().unwrap_or(0);
+
+    for level in (0..max_height).rev() {
+        let cells: Vec<_> = rendered_stacks
+            .iter()
+            .map(|rendered_stack| match rendered_stack.get(level) {
+                Some(rendered_crate) => format!("{rendered_crate:^cell_width$}"),
+                None => " ".repeat(cell_width),
+            })
+            .collect();
+
+        writeln!(s, "{}", cells.join(" ")).unwrap();
+    }
+
+    // stacks are counted 1-based in the drawing, the extra space on the left
+    // keeps single digits below the label of a three character cell
+    let stack_numbers: Vec<_> = (1..=stacks.len())
+        .map(|stack_number| {
+            let number = stack_number.to_string();
+            let left_padding = (cell_width - number.len()).div_ceil(2);
+            format!(
+                "{:left_padding$}{number:<width$}",
+                "",
+                width = cell_width - left_padding
+            )
+        })
+        .collect();
+
+    writeln!(s, "{}", stack_numbers.join(" ")).unwrap();
+
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::render_drawing;
+    use crate::input::{parse_stacks, Stack};
+
+    #[test]
+    fn render_example() {
+        let stacks = vec![
+            Stack {
+                elements: vec![String::from("Z"), String::from("N")],
+            },
+            Stack {
+                elements: vec![String::from("M"), String::from("C"), String::from("D")],
+            },
+            Stack {
+                elements: vec![String::from("P")],
+            },
+        ];
+
+        let expected = "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 \n";
+
+        assert_eq!(render_drawing(&stacks), expected);
+    }
+
+    #[test]
+    fn render_wide_example() {
+        let stacks: Vec<_> = (1..=10)
+            .map(|stack_number| Stack {
+                elements: if stack_number == 2 {
+                    vec![String::from("LONG")]
+                } else {
+                    vec![]
+                },
+            })
+            .collect();
+
+        let expected = concat!(
+            "       [LONG]                                                        \n",
+            "   1      2      3      4      5      6      7      8      9     10  \n"
+        );
+
+        assert_eq!(render_drawing(&stacks), expected);
+    }
+
+    fn stacks() -> impl Strategy<Value = Vec<Stack>> {
+        let stack =
+            prop::collection::vec("[A-Z]{1,3}", 0..8).prop_map(|elements| Stack { elements });
+
+        prop::collection::vec(stack, 1..15)
+    }
+
+    proptest! {
+        #[test]
+        fn parse_rendered_drawing(stacks in stacks()) {
+            let drawing = render_drawing(&stacks);
+            prop_assert_eq!(parse_stacks(&drawing), Ok(stacks));
+        }
+    }
+}
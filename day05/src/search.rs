@@ -0,0 +1,202 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Display;
+
+use crate::crane::Crane;
+use crate::input::{Crate, Move, Stack};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum SearchError {
+    TargetLengthMismatch {
+        target_len: usize,
+        num_stacks: usize,
+    },
+    Unreachable,
+    StateLimitReached {
+        max_states: usize,
+    },
+}
+
+impl Display for SearchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use SearchError::*;
+
+        match self {
+            TargetLengthMismatch {
+                target_len,
+                num_stacks,
+            } => write!(
+                f,
+                "target has {target_len} top crates, but there are {num_stacks} stacks"
+            ),
+            Unreachable => write!(f, "no move sequence produces the target top crates"),
+            StateLimitReached { max_states } => {
+                write!(f, "gave up after visiting {max_states} stack states")
+            }
+        }
+    }
+}
+
+struct VisitedState {
+    stacks: Vec<Stack>,
+    // index of the state this one was first reached from, and the move used for it
+    predecessor: Option<(usize, Move)>,
+}
+
+// Breadth first search over all stack states reachable with the given crane, so the
+// first state with the target top crates is reached by a shortest move sequence.
+pub fn shortest_move_sequence<C: Crane>(
+    stacks: &[Stack],
+    target: &[Crate],
+    crane: &C,
+    max_states: usize,
+) -> Result<Vec<Move>, SearchError> {
+    if target.len() != stacks.len() {
+        return Err(SearchError::TargetLengthMismatch {
+            target_len: target.len(),
+            num_stacks: stacks.len(),
+        });
+    }
+
+    if !has_enough_crates_for(stacks, target) {
+        return Err(SearchError::Unreachable);
+    }
+
+    let mut visited_states = vec![VisitedState {
+        stacks: stacks.to_vec(),
+        predecessor: None,
+    }];
+    let mut state_ids = HashMap::from([(stacks.to_vec(), 0)]);
+    let mut queue = VecDeque::from([0]);
+
+    while let Some(state_id) = queue.pop_front() {
+        let current_stacks = visited_states[state_id].stacks.clone();
+
+        if has_top_crates(&current_stacks, target) {
+            return Ok(reconstruct_moves(&visited_states, state_id));
+        }
+
+        for move_ in possible_moves(&current_stacks) {
+            let mut next_stacks = current_stacks.clone();
+            crane
+                .apply_move(&mut next_stacks, &move_)
+                .expect("Only valid moves are generated");
+
+            if state_ids.contains_key(&next_stacks) {
+                continue;
+            }
+
+            if visited_states.len() == max_states {
+                return Err(SearchError::StateLimitReached { max_states });
+            }
+
+            let next_state_id = visited_states.len();
+            state_ids.insert(next_stacks.clone(), next_state_id);
+            visited_states.push(VisitedState {
+                stacks: next_stacks,
+                predecessor: Some((state_id, move_)),
+            });
+            queue.push_back(next_state_id);
+        }
+    }
+
+    Err(SearchError::Unreachable)
+}
+
+fn has_enough_crates_for(stacks: &[Stack], target: &[Crate]) -> bool {
+    let mut crate_counts: HashMap<&Crate, usize> = HashMap::new();
+    for crate_ in stacks.iter().flat_map(|stack| stack.iter()) {
+        *crate_counts.entry(crate_).or_default() += 1;
+    }
+
+    target
+        .iter()
+        .all(|crate_| match crate_counts.get_mut(crate_) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                true
+            }
+            _ => false,
+        })
+}
+
+fn has_top_crates(stacks: &[Stack], target: &[Crate]) -> bool {
+    stacks
+        .iter()
+        .zip(target)
+        .all(|(stack, target_crate)| stack.last() == Some(target_crate))
+}
+
+fn possible_moves(stacks: &[Stack]) -> impl Iterator<Item = Move> + '_ {
+    let num_stacks = stacks.len();
+
+    (0..num_stacks).flat_map(move |index_from| {
+        (0..num_stacks)
+            .filter(move |&index_to| index_to != index_from)
+            .flat_map(move |index_to| {
+                (1..=stacks[index_from].len()).map(move |amount| Move {
+                    index_from,
+                    index_to,
+                    amount,
+                })
+            })
+    })
+}
+
+fn reconstruct_moves(visited_states: &[VisitedState], mut state_id: usize) -> Vec<Move> {
+    let mut moves = Vec::new();
+
+    while let Some((predecessor_id, move_)) = &visited_states[state_id].predecessor {
+        moves.push(move_.clone());
+        state_id = *predecessor_id;
+    }
+
+    moves.reverse();
+    moves
+}
+
+mod tests {
+    #[test]
+    fn example_targets() {
+        use super::{shortest_move_sequence, SearchError};
+        use crate::crane::{Crane, CrateMover9000, CrateMover9001};
+        use crate::input::Stack;
+
+        let labels = |s: &str| s.chars().map(String::from).collect::<Vec<_>>();
+        let stacks = vec![
+            Stack {
+                elements: labels("ZN"),
+            },
+            Stack {
+                elements: labels("MCD"),
+            },
+            Stack {
+                elements: labels("P"),
+            },
+        ];
+
+        let moves =
+            shortest_move_sequence(&stacks, &labels("CMZ"), &CrateMover9000, 100_000).unwrap();
+        let mut moved_stacks = stacks.clone();
+        CrateMover9000
+            .apply_moves(&mut moved_stacks, &moves)
+            .unwrap();
+        let top_crates: String = moved_stacks
+            .iter()
+            .map(|s| s.last().unwrap().as_str())
+            .collect();
+
+        assert_eq!(top_crates, "CMZ");
+        // one move changes at most two top crates, but all three have to change
+        assert_eq!(moves.len(), 2);
+
+        assert_eq!(
+            shortest_move_sequence(&stacks, &labels("NDP"), &CrateMover9001, 100_000)
+                .map(|m| m.len()),
+            Ok(0)
+        );
+        assert_eq!(
+            shortest_move_sequence(&stacks, &labels("XDP"), &CrateMover9001, 100_000),
+            Err(SearchError::Unreachable)
+        );
+    }
+}
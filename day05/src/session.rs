@@ -0,0 +1,246 @@
+use crate::crane::{Crane, MoveError};
+use crate::input::{Crate, Move, Stack};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrackedCrate {
+    pub label: Crate,
+    // 1-based like the lines of the move list
+    pub last_move_number: Option<usize>,
+}
+
+// Walks back and forth through a move list. The states after every
+// snapshot_interval moves are kept, so any state can be restored by replaying at
+// most snapshot_interval moves from the closest snapshot before it.
+pub struct Session<'a, C: Crane> {
+    crane: &'a C,
+    moves: &'a [Move],
+    snapshot_interval: usize,
+    // the state after i * snapshot_interval moves, filled in while moving forward
+    snapshots: Vec<Vec<Stack<TrackedCrate>>>,
+    current_stacks: Vec<Stack<TrackedCrate>>,
+    num_applied: usize,
+}
+
+impl<'a, C: Crane> Session<'a, C> {
+    // O(sqrt(n)) snapshots balance the memory use against the time of a jump
+    pub fn new(stacks: &[Stack], moves: &'a [Move], crane: &'a C) -> Self {
+        let snapshot_interval = (moves.len() as f64).sqrt().ceil().max(1.0) as usize;
+        Self::with_snapshot_interval(stacks, moves, crane, snapshot_interval)
+    }
+
+    pub fn with_snapshot_interval(
+        stacks: &[Stack],
+        moves: &'a [Move],
+        crane: &'a C,
+        snapshot_interval: usize,
+    ) -> Self {
+        assert!(
+            snapshot_interval > 0,
+            "Snapshot interval has to be positive"
+        );
+
+        let initial_stacks: Vec<_> = stacks
+            .iter()
+            .map(|stack| Stack {
+                elements: stack
+                    .iter()
+                    .map(|label| TrackedCrate {
+                        label: label.clone(),
+                        last_move_number: None,
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        Session {
+            crane,
+            moves,
+            snapshot_interval,
+            snapshots: vec![initial_stacks.clone()],
+            current_stacks: initial_stacks,
+            num_applied: 0,
+        }
+    }
+
+    pub fn num_applied(&self) -> usize {
+        self.num_applied
+    }
+
+    pub fn num_moves(&self) -> usize {
+        self.moves.len()
+    }
+
+    pub fn stacks(&self) -> Vec<Stack> {
+        self.current_stacks
+            .iter()
+            .map(|stack| Stack {
+                elements: stack.iter().map(|tracked| tracked.label.clone()).collect(),
+            })
+            .collect()
+    }
+
+    // level 0 is the bottom of the stack
+    pub fn crate_at(&self, index_stack: usize, level: usize) -> Option<&TrackedCrate> {
+        self.current_stacks.get(index_stack)?.get(level)
+    }
+
+    // returns false if all moves are already applied
+    pub fn step_forward(&mut self) -> Result<bool, MoveError> {
+        let Some(move_) = self.moves.get(self.num_applied) else {
+            return Ok(false);
+        };
+
+        let move_number = self.num_applied + 1;
+
+        let stack_to_height = self
+            .current_stacks
+            .get(move_.index_to)
+            .map_or(0, |stack| stack.len());
+        self.crane
+            .apply_move(&mut self.current_stacks, move_)
+            .map_err(|reason| MoveError {
+                move_number,
+                reason,
+            })?;
+
+        for moved_crate in &mut self.current_stacks[move_.index_to][stack_to_height..] {
+            moved_crate.last_move_number = Some(move_number);
+        }
+
+        self.num_applied = move_number;
+
+        let is_snapshot_position = self.num_applied.is_multiple_of(self.snapshot_interval);
+        let is_next_snapshot = self.num_applied / self.snapshot_interval == self.snapshots.len();
+        if is_snapshot_position && is_next_snapshot {
+            self.snapshots.push(self.current_stacks.clone());
+        }
+
+        Ok(true)
+    }
+
+    // returns false if no moves are applied
+    pub fn step_backward(&mut self) -> bool {
+        if self.num_applied == 0 {
+            return false;
+        }
+
+        self.jump_to(self.num_applied - 1)
+            .expect("Moves before the current one were already applied successfully");
+
+        true
+    }
+
+    // on an invalid move, the session stays at the state right before it
+    pub fn jump_to(&mut self, num_applied: usize) -> Result<(), MoveError> {
+        assert!(
+            num_applied <= self.moves.len(),
+            "Cannot jump past the last move"
+        );
+
+        let snapshot_idx = (num_applied / self.snapshot_interval).min(self.snapshots.len() - 1);
+        let snapshot_num_applied = snapshot_idx * self.snapshot_interval;
+
+        // the current state is a better starting point if it lies between the snapshot and the target
+        if !(snapshot_num_applied..=num_applied).contains(&self.num_applied) {
+            self.current_stacks = self.snapshots[snapshot_idx].clone();
+            self.num_applied = snapshot_num_applied;
+        }
+
+        while self.num_applied < num_applied {
+            self.step_forward()?;
+        }
+
+        Ok(())
+    }
+}
+
+mod tests {
+    #[test]
+    fn jumps_match_linear_replay() {
+        use super::Session;
+        use crate::crane::{Crane, CrateMover9000};
+        use crate::input::{Move, Stack};
+
+        let stacks = vec![
+            Stack {
+                elements: vec![String::from("A"), String::from("B"), String::from("C")],
+            },
+            Stack {
+                elements: vec![String::from("D")],
+            },
+            Stack { elements: vec![] },
+        ];
+        let moves: Vec<_> = (0..20)
+            .map(|i| Move {
+                index_from: i % 3,
+                index_to: (i + 1) % 3,
+                amount: 1,
+            })
+            .collect();
+
+        // only replay prefixes that are valid
+        let mut valid_moves = Vec::new();
+        let mut replayed_stacks = stacks.clone();
+        let mut states = vec![replayed_stacks.clone()];
+        for move_ in moves {
+            if CrateMover9000
+                .apply_move(&mut replayed_stacks, &move_)
+                .is_ok()
+            {
+                valid_moves.push(move_);
+                states.push(replayed_stacks.clone());
+            }
+        }
+
+        let mut session =
+            Session::with_snapshot_interval(&stacks, &valid_moves, &CrateMover9000, 3);
+
+        for num_applied in [7, 2, 13, 0, valid_moves.len(), 5, 6] {
+            session.jump_to(num_applied).unwrap();
+            assert_eq!(session.stacks(), states[num_applied]);
+        }
+
+        assert!(session.step_backward());
+        assert_eq!(session.stacks(), states[5]);
+        assert!(session.step_forward().unwrap());
+        assert_eq!(session.stacks(), states[6]);
+    }
+
+    #[test]
+    fn last_move_touching_crate() {
+        use super::Session;
+        use crate::crane::CrateMover9001;
+        use crate::input::{Move, Stack};
+
+        let stacks = vec![
+            Stack {
+                elements: vec![String::from("A"), String::from("B"), String::from("C")],
+            },
+            Stack { elements: vec![] },
+        ];
+        let moves = [
+            Move {
+                index_from: 0,
+                index_to: 1,
+                amount: 2,
+            },
+            Move {
+                index_from: 1,
+                index_to: 0,
+                amount: 1,
+            },
+        ];
+
+        let mut session = Session::new(&stacks, &moves, &CrateMover9001);
+        session.jump_to(2).unwrap();
+
+        let last_move_number = |index_stack, level| {
+            let tracked = session.crate_at(index_stack, level).unwrap();
+            (tracked.label.as_str(), tracked.last_move_number)
+        };
+
+        assert_eq!(last_move_number(0, 0), ("A", None));
+        assert_eq!(last_move_number(0, 1), ("C", Some(2)));
+        assert_eq!(last_move_number(1, 0), ("B", Some(1)));
+    }
+}
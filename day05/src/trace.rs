@@ -0,0 +1,201 @@
+use std::fmt::Display;
+use std::io::{self, Write};
+use std::thread;
+use std::time::Duration;
+
+use crate::crane::{Crane, MoveError};
+use crate::input::{Crate, Move, Stack};
+use crate::rendering::render_drawing_with;
+
+pub struct TraceOptions {
+    pub moves_per_frame: usize,
+    // redraws the frames in place instead of printing them one after another
+    pub frame_delay: Option<Duration>,
+}
+
+#[derive(Clone)]
+struct TracedCrate {
+    label: Crate,
+    moved_since_last_frame: bool,
+}
+
+#[derive(Debug)]
+pub enum TraceError {
+    Io(io::Error),
+    Move(MoveError),
+}
+
+impl From<io::Error> for TraceError {
+    fn from(error: io::Error) -> Self {
+        TraceError::Io(error)
+    }
+}
+
+impl Display for TraceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TraceError::Io(error) => write!(f, "failed to write trace: {error}"),
+            TraceError::Move(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+// Crates that were moved since the previous frame are drawn with round brackets.
+// An invalid move ends the trace with a frame of the stacks right before it.
+pub fn trace<C: Crane, W: Write>(
+    stacks: &[Stack],
+    moves: &[Move],
+    crane: &C,
+    options: &TraceOptions,
+    out: &mut W,
+) -> Result<(), TraceError> {
+    assert!(
+        options.moves_per_frame > 0,
+        "Need at least one move per frame"
+    );
+
+    let mut traced_stacks: Vec<Stack<TracedCrate>> = stacks
+        .iter()
+        .map(|stack| Stack {
+            elements: stack
+                .iter()
+                .map(|label| TracedCrate {
+                    label: label.clone(),
+                    moved_since_last_frame: false,
+                })
+                .collect(),
+        })
+        .collect();
+
+    write_frame(out, options, "initial stacks", &mut traced_stacks)?;
+
+    for (move_idx, move_) in moves.iter().enumerate() {
+        let num_applied = move_idx + 1;
+
+        // only the moved crates end up above the untouched ones on the target stack
+        let stack_to_height = traced_stacks
+            .get(move_.index_to)
+            .map_or(0, |stack| stack.len());
+        if let Err(reason) = crane.apply_move(&mut traced_stacks, move_) {
+            let error = MoveError {
+                move_number: num_applied,
+                reason,
+            };
+
+            write_frame(out, options, &error.to_string(), &mut traced_stacks)?;
+            return Err(TraceError::Move(error));
+        }
+
+        for moved_crate in &mut traced_stacks[move_.index_to][stack_to_height..] {
+            moved_crate.moved_since_last_frame = true;
+        }
+
+        if num_applied % options.moves_per_frame == 0 || num_applied == moves.len() {
+            let title = format!("after move {num_applied} of {} ({move_})", moves.len());
+            write_frame(out, options, &title, &mut traced_stacks)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn write_frame<W: Write>(
+    out: &mut W,
+    options: &TraceOptions,
+    title: &str,
+    traced_stacks: &mut [Stack<TracedCrate>],
+) -> io::Result<()> {
+    let drawing = render_drawing_with(traced_stacks, |traced_crate| {
+        if traced_crate.moved_since_last_frame {
+            format!("({})", traced_crate.label)
+        } else {
+            format!("[{}]", traced_crate.label)
+        }
+    });
+
+    if options.frame_delay.is_some() {
+        // clear the terminal and move the cursor to the top left
+        write!(out, "\x1b[2J\x1b[H")?;
+    }
+
+    writeln!(out, "{title}")?;
+    writeln!(out, "{drawing}")?;
+    out.flush()?;
+
+    for traced_crate in traced_stacks.iter_mut().flat_map(|stack| stack.iter_mut()) {
+        traced_crate.moved_since_last_frame = false;
+    }
+
+    if let Some(frame_delay) = options.frame_delay {
+        thread::sleep(frame_delay);
+    }
+
+    Ok(())
+}
+
+mod tests {
+    #[test]
+    fn trace_frames() {
+        use super::{trace, TraceError, TraceOptions};
+        use crate::crane::{CrateMover9000, CrateMover9001, MoveErrorReason};
+        use crate::input::{Move, Stack};
+
+        let stacks = vec![
+            Stack {
+                elements: vec![String::from("A"), String::from("B")],
+            },
+            Stack {
+                elements: vec![String::from("C")],
+            },
+        ];
+        let moves = [
+            Move {
+                amount: 2,
+                index_from: 0,
+                index_to: 1,
+            },
+            Move {
+                amount: 1,
+                index_from: 1,
+                index_to: 0,
+            },
+            Move {
+                amount: 1,
+                index_from: 0,
+                index_to: 2,
+            },
+        ];
+        let options = TraceOptions {
+            moves_per_frame: 2,
+            frame_delay: None,
+        };
+
+        // the frame after the first move is skipped, both moves are highlighted
+        let mut out = Vec::new();
+        trace(&stacks, &moves[..2], &CrateMover9000, &options, &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "initial stacks\n[B]    \n[A] [C]\n 1   2 \n\n\
+             after move 2 of 2 (move 1 from 2 to 1)\n    (B)\n(A) [C]\n 1   2 \n\n"
+        );
+
+        // the invalid move gets a frame of its own without highlighted crates
+        let mut out = Vec::new();
+        let result = trace(&stacks, &moves, &CrateMover9001, &options, &mut out);
+        assert!(matches!(
+            result,
+            Err(TraceError::Move(error)) if error.move_number == 3
+                && error.reason == MoveErrorReason::StackOutOfRange {
+                    stack_number: 3,
+                    num_stacks: 2
+                }
+        ));
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "initial stacks\n[B]    \n[A] [C]\n 1   2 \n\n\
+             after move 2 of 3 (move 1 from 2 to 1)\n    (A)\n(B) [C]\n 1   2 \n\n\
+             invalid move number 3: stack 3 does not exist, there are only 2 stacks\n    \
+             [A]\n[B] [C]\n 1   2 \n\n"
+        );
+    }
+}
@@ -0,0 +1,258 @@
+use std::mem;
+
+use crate::crane::CrateStack;
+
+type Link<T> = Option<Box<Node<T>>>;
+
+struct Node<T> {
+    crate_: T,
+    priority: u64,
+    size: usize,
+    // the order of the whole subtree below this node still has to be reversed
+    reversed: bool,
+    left: Link<T>,
+    right: Link<T>,
+}
+
+impl<T> Node<T> {
+    fn update_size(&mut self) {
+        self.size = 1 + size(&self.left) + size(&self.right);
+    }
+
+    fn push_down_reversal(&mut self) {
+        if self.reversed {
+            mem::swap(&mut self.left, &mut self.right);
+
+            for child in [&mut self.left, &mut self.right].into_iter().flatten() {
+                child.reversed = !child.reversed;
+            }
+
+            self.reversed = false;
+        }
+    }
+}
+
+fn size<T>(link: &Link<T>) -> usize {
+    link.as_ref().map_or(0, |node| node.size)
+}
+
+// all crates of the lower tree end up below the crates of the upper tree
+fn merge<T>(lower: Link<T>, upper: Link<T>) -> Link<T> {
+    match (lower, upper) {
+        (None, upper) => upper,
+        (lower, None) => lower,
+        (Some(mut lower), Some(mut upper)) => {
+            if lower.priority > upper.priority {
+                lower.push_down_reversal();
+                lower.right = merge(lower.right.take(), Some(upper));
+                lower.update_size();
+                Some(lower)
+            } else {
+                upper.push_down_reversal();
+                upper.left = merge(Some(lower), upper.left.take());
+                upper.update_size();
+                Some(upper)
+            }
+        }
+    }
+}
+
+// returns the lowest num_lower crates and the rest
+fn split<T>(link: Link<T>, num_lower: usize) -> (Link<T>, Link<T>) {
+    let Some(mut node) = link else {
+        return (None, None);
+    };
+
+    node.push_down_reversal();
+    let left_size = size(&node.left);
+
+    if num_lower <= left_size {
+        let (lower, upper) = split(node.left.take(), num_lower);
+        node.left = upper;
+        node.update_size();
+        (lower, Some(node))
+    } else {
+        let (lower, upper) = split(node.right.take(), num_lower - left_size - 1);
+        node.right = lower;
+        node.update_size();
+        (Some(node), upper)
+    }
+}
+
+// An implicit treap, the position of a crate in the stack is its position in the
+// in-order traversal. Splitting, concatenating and reversing take expected
+// O(log n) time, independent of the number of moved crates.
+pub struct TreapStack<T> {
+    root: Link<T>,
+}
+
+impl<T> TreapStack<T> {
+    pub fn new() -> Self {
+        TreapStack { root: None }
+    }
+
+    pub fn push(&mut self, crate_: T) {
+        let node = Box::new(Node {
+            crate_,
+            priority: rand::random(),
+            size: 1,
+            reversed: false,
+            left: None,
+            right: None,
+        });
+
+        self.root = merge(self.root.take(), Some(node));
+    }
+
+    pub fn last(&self) -> Option<&T> {
+        let mut node = self.root.as_ref()?;
+        let mut reversed = false;
+
+        loop {
+            reversed ^= node.reversed;
+
+            let upper_child = if reversed { &node.left } else { &node.right };
+            match upper_child {
+                Some(child) => node = child,
+                None => return Some(&node.crate_),
+            }
+        }
+    }
+
+    pub fn to_vec(&self) -> Vec<T>
+    where
+        T: Clone,
+    {
+        fn collect<T: Clone>(link: &Link<T>, reversed: bool, crates: &mut Vec<T>) {
+            let Some(node) = link else {
+                return;
+            };
+
+            let reversed = reversed ^ node.reversed;
+            let (lower, upper) = if reversed {
+                (&node.right, &node.left)
+            } else {
+                (&node.left, &node.right)
+            };
+
+            collect(lower, reversed, crates);
+            crates.push(node.crate_.clone());
+            collect(upper, reversed, crates);
+        }
+
+        let mut crates = Vec::with_capacity(self.num_crates());
+        collect(&self.root, false, &mut crates);
+        crates
+    }
+}
+
+impl<T> FromIterator<T> for TreapStack<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut stack = TreapStack::new();
+
+        for crate_ in iter {
+            stack.push(crate_);
+        }
+
+        stack
+    }
+}
+
+impl<T> CrateStack for TreapStack<T> {
+    fn num_crates(&self) -> usize {
+        size(&self.root)
+    }
+
+    fn split_off_top(&mut self, amount: usize) -> Self {
+        let num_remaining = self.num_crates() - amount;
+        let (lower, upper) = split(self.root.take(), num_remaining);
+
+        self.root = lower;
+        TreapStack { root: upper }
+    }
+
+    fn put_on_top(&mut self, crates: Self) {
+        self.root = merge(self.root.take(), crates.root);
+    }
+
+    fn reverse(&mut self) {
+        if let Some(root) = &mut self.root {
+            root.reversed = !root.reversed;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::TreapStack;
+    use crate::crane::{CapacityLimitedCrane, Crane, CrateMover9000, CrateMover9001};
+    use crate::input::{Move, Stack};
+
+    // moves that are valid for the given heights, they do not depend on the crane
+    fn stacks_and_moves() -> impl Strategy<Value = (Vec<Vec<u32>>, Vec<Move>)> {
+        let heights = prop::collection::vec(0..20usize, 2..5);
+        let raw_moves =
+            prop::collection::vec((any::<usize>(), any::<usize>(), any::<usize>()), 0..40);
+
+        (heights, raw_moves).prop_map(|(heights, raw_moves)| {
+            let mut next_crate = 0;
+            let stacks: Vec<Vec<u32>> = heights
+                .iter()
+                .map(|&height| {
+                    next_crate += height as u32;
+                    (next_crate - height as u32..next_crate).collect()
+                })
+                .collect();
+
+            let mut heights = heights;
+            let mut moves = Vec::new();
+            for (from, to, amount) in raw_moves {
+                let index_from = from % heights.len();
+                let index_to = to % heights.len();
+                let amount = amount % (heights[index_from] + 1);
+
+                heights[index_from] -= amount;
+                heights[index_to] += amount;
+                moves.push(Move {
+                    index_from,
+                    index_to,
+                    amount,
+                });
+            }
+
+            (stacks, moves)
+        })
+    }
+
+    fn assert_same_result<C: Crane>(crane: &C, stacks: &[Vec<u32>], moves: &[Move]) {
+        let mut vec_stacks: Vec<_> = stacks
+            .iter()
+            .map(|crates| Stack {
+                elements: crates.clone(),
+            })
+            .collect();
+        let mut treap_stacks: Vec<_> = stacks
+            .iter()
+            .map(|crates| crates.iter().copied().collect::<TreapStack<_>>())
+            .collect();
+
+        crane.apply_moves(&mut vec_stacks, moves).unwrap();
+        crane.apply_moves(&mut treap_stacks, moves).unwrap();
+
+        for (vec_stack, treap_stack) in vec_stacks.iter().zip(&treap_stacks) {
+            assert_eq!(vec_stack.elements, treap_stack.to_vec());
+            assert_eq!(vec_stack.last(), treap_stack.last());
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn treap_matches_vec((stacks, moves) in stacks_and_moves()) {
+            assert_same_result(&CrateMover9000, &stacks, &moves);
+            assert_same_result(&CrateMover9001, &stacks, &moves);
+            assert_same_result(&CapacityLimitedCrane::new(3), &stacks, &moves);
+        }
+    }
+}
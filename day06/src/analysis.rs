@@ -0,0 +1,85 @@
+use hashbag::HashBag;
+
+// same sliding window as in the search for the first marker, but it keeps going
+pub fn all_marker_end_offsets(ascii_chars: &[u8], num_consecutive: usize) -> Vec<usize> {
+    if num_consecutive == 0 || ascii_chars.len() < num_consecutive {
+        return Vec::new();
+    }
+
+    let (first_window, after_first_window) = ascii_chars.split_at(num_consecutive);
+    let mut current_chars: HashBag<_> = first_window.iter().collect();
+    let mut end_offsets = Vec::new();
+
+    if current_chars.set_len() == num_consecutive {
+        end_offsets.push(num_consecutive);
+    }
+
+    let window_border_iter = ascii_chars.iter().zip(after_first_window.iter());
+
+    for (window_idx, (oldest_current_char, next_char)) in window_border_iter.enumerate() {
+        current_chars.remove(oldest_current_char);
+        current_chars.insert(next_char);
+
+        if current_chars.set_len() == num_consecutive {
+            // the window now ends after the next char
+            end_offsets.push(window_idx + num_consecutive + 1);
+        }
+    }
+
+    end_offsets
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct DistinctRun {
+    pub start: usize,
+    pub len: usize,
+}
+
+// The window grows at the end and shrinks at the start until the next char is not
+// in it anymore. The first of several longest runs is returned.
+pub fn longest_distinct_run(ascii_chars: &[u8]) -> DistinctRun {
+    let mut current_chars = HashBag::new();
+    let mut start = 0;
+    let mut longest = DistinctRun { start: 0, len: 0 };
+
+    for (end, next_char) in ascii_chars.iter().enumerate() {
+        while current_chars.contains(next_char) > 0 {
+            current_chars.remove(&ascii_chars[start]);
+            start += 1;
+        }
+
+        current_chars.insert(next_char);
+
+        let len = end + 1 - start;
+        if len > longest.len {
+            longest = DistinctRun { start, len };
+        }
+    }
+
+    longest
+}
+
+mod tests {
+    #[test]
+    fn example_analysis() {
+        use super::{all_marker_end_offsets, longest_distinct_run, DistinctRun};
+
+        let datastream = b"mjqjpqmgbljsphdztnvjfqwrcgsmlb";
+
+        let end_offsets = all_marker_end_offsets(datastream, 14);
+        assert_eq!(end_offsets.first(), Some(&19));
+        assert!(end_offsets.iter().all(|&end| {
+            let window = &datastream[end - 14..end];
+            (1..14).all(|i| !window[..i].contains(&window[i]))
+        }));
+
+        assert_eq!(all_marker_end_offsets(b"abcabcab", 3), [3, 4, 5, 6, 7, 8]);
+        assert_eq!(all_marker_end_offsets(b"aab", 3), []);
+
+        assert_eq!(
+            longest_distinct_run(datastream),
+            DistinctRun { start: 12, len: 18 }
+        );
+        assert_eq!(longest_distinct_run(b""), DistinctRun { start: 0, len: 0 });
+    }
+}
@@ -0,0 +1,66 @@
+use std::time::{Duration, Instant};
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::bitmask_detector::index_after_first_n_consecutive_distinct_bytes;
+use crate::{
+    index_after_first_n_consecutive_distict_chars, index_after_first_n_consecutive_distinct_symbols,
+};
+
+const NUM_CONSECUTIVE: usize = 14;
+
+// Buffers of 1 KB, 32 KB, 1 MB, 32 MB and 1 GB up to the given size. The datastreams
+// only use 13 letters, so they contain no start-of-message marker and every detector
+// has to scan the whole buffer.
+pub fn run(max_num_bytes: usize, seed: u64) {
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for num_bytes in [1 << 10, 1 << 15, 1 << 20, 1 << 25, 1 << 30] {
+        if num_bytes > max_num_bytes {
+            break;
+        }
+
+        let bytes: Vec<u8> = (0..num_bytes)
+            .map(|_| rng.gen_range(b'a'..b'a' + NUM_CONSECUTIVE as u8 - 1))
+            .collect();
+
+        let hash_bag_duration = time_detector(&bytes, |bytes| {
+            index_after_first_n_consecutive_distinct_symbols(bytes, NUM_CONSECUTIVE)
+        });
+        let counter_duration = time_detector(&bytes, |bytes| {
+            index_after_first_n_consecutive_distict_chars(bytes, NUM_CONSECUTIVE)
+        });
+        let bitmask_duration = time_detector(&bytes, |bytes| {
+            index_after_first_n_consecutive_distinct_bytes(bytes, NUM_CONSECUTIVE)
+        });
+
+        println!(
+            "{:>5}: HashBag {hash_bag_duration:.3?}, byte counter {counter_duration:.3?}, bitmask {bitmask_duration:.3?}",
+            format_size(num_bytes)
+        );
+    }
+}
+
+fn time_detector<F>(bytes: &[u8], detector: F) -> Duration
+where
+    F: Fn(&[u8]) -> Result<usize, String>,
+{
+    let start = Instant::now();
+    let result = detector(bytes);
+    let duration = start.elapsed();
+
+    assert!(
+        result.is_err(),
+        "The generated datastream contains a marker"
+    );
+    duration
+}
+
+fn format_size(num_bytes: usize) -> String {
+    match num_bytes {
+        _ if num_bytes >= 1 << 30 => format!("{} GB", num_bytes >> 30),
+        _ if num_bytes >= 1 << 20 => format!("{} MB", num_bytes >> 20),
+        _ => format!("{} KB", num_bytes >> 10),
+    }
+}
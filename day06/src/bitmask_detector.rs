@@ -0,0 +1,103 @@
+// Allocation-free alternative to the sliding windows: the window is checked from its
+// end backwards with a bitmask of the bytes seen so far. No window that contains both
+// copies of a repeated byte can be a marker, so the search skips ahead to the window
+// that starts right after the earlier copy.
+pub fn index_after_first_n_consecutive_distinct_bytes(
+    bytes: &[u8],
+    num_consecutive: usize,
+) -> Result<usize, String> {
+    let mut window_start = 0;
+
+    while window_start + num_consecutive <= bytes.len() {
+        let window = &bytes[window_start..window_start + num_consecutive];
+
+        match last_repeated_position(window) {
+            Some(repeated_idx) => window_start += repeated_idx + 1,
+            None => return Ok(window_start + num_consecutive),
+        }
+    }
+
+    Err(format!(
+        "slice does not contain {num_consecutive} distinct bytes"
+    ))
+}
+
+// position of the last byte of the window that occurs again later in the window
+fn last_repeated_position(window: &[u8]) -> Option<usize> {
+    let mut seen = [0u64; 4];
+
+    window.iter().enumerate().rev().find_map(|(idx, &byte)| {
+        let (word, bit) = (byte as usize / 64, 1 << (byte % 64));
+
+        if seen[word] & bit != 0 {
+            return Some(idx);
+        }
+
+        seen[word] |= bit;
+        None
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::index_after_first_n_consecutive_distinct_bytes;
+    use crate::{
+        index_after_first_n_consecutive_distict_chars,
+        index_after_first_n_consecutive_distinct_symbols,
+    };
+
+    #[test]
+    fn example_datastreams() {
+        for (datastream, start_of_packet, start_of_message) in [
+            ("mjqjpqmgbljsphdztnvjfqwrcgsmlb", 7, 19),
+            ("bvwbjplbgvbhsrlpgdmjqwftvncz", 5, 23),
+            ("nppdvjthqldpwncqszvftbrmjlhg", 6, 23),
+            ("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg", 10, 29),
+            ("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", 11, 26),
+        ] {
+            let bytes = datastream.as_bytes();
+
+            assert_eq!(
+                index_after_first_n_consecutive_distinct_bytes(bytes, 4),
+                Ok(start_of_packet)
+            );
+            assert_eq!(
+                index_after_first_n_consecutive_distinct_bytes(bytes, 14),
+                Ok(start_of_message)
+            );
+        }
+    }
+
+    // small alphabets, so that markers are rare and the windows often contain repeats
+    fn datastream() -> impl Strategy<Value = Vec<u8>> {
+        (1..=20u8).prop_flat_map(|alphabet_size| {
+            prop::collection::vec((0..alphabet_size).prop_map(|offset| b'a' + offset), 0..200)
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn all_detectors_agree(bytes in datastream(), num_consecutive in 0..20usize) {
+            let bitmask_result = index_after_first_n_consecutive_distinct_bytes(&bytes, num_consecutive);
+
+            prop_assert_eq!(
+                bitmask_result.clone().ok(),
+                index_after_first_n_consecutive_distinct_symbols(&bytes, num_consecutive).ok()
+            );
+            prop_assert_eq!(
+                bitmask_result.ok(),
+                index_after_first_n_consecutive_distict_chars(&bytes, num_consecutive).ok()
+            );
+        }
+
+        #[test]
+        fn all_byte_values(bytes in prop::collection::vec(any::<u8>(), 0..600), num_consecutive in 0..300usize) {
+            prop_assert_eq!(
+                index_after_first_n_consecutive_distinct_bytes(&bytes, num_consecutive).ok(),
+                index_after_first_n_consecutive_distinct_symbols(&bytes, num_consecutive).ok()
+            );
+        }
+    }
+}
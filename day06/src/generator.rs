@@ -0,0 +1,213 @@
+use std::collections::HashSet;
+use std::fmt::Display;
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+const START_OF_PACKET_SIZE: usize = 4;
+const START_OF_MESSAGE_SIZE: usize = 14;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum GeneratorError {
+    AlphabetTooSmall { num_distinct: usize },
+    StartOfPacketTooEarly { start_of_packet: usize },
+    StartOfMessageTooEarly { start_of_message: usize },
+    StreamTooShort { len: usize },
+}
+
+impl Display for GeneratorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GeneratorError::AlphabetTooSmall { num_distinct } => write!(
+                f,
+                "the alphabet has only {num_distinct} distinct bytes, \
+                 but a start-of-message marker needs {START_OF_MESSAGE_SIZE}"
+            ),
+            GeneratorError::StartOfPacketTooEarly { start_of_packet } => write!(
+                f,
+                "a start-of-packet marker cannot end after {start_of_packet} bytes, \
+                 it consists of {START_OF_PACKET_SIZE} bytes"
+            ),
+            // the message marker contains a packet marker that ends 10 bytes earlier
+            GeneratorError::StartOfMessageTooEarly { start_of_message } => write!(
+                f,
+                "the start-of-message marker cannot end after {start_of_message} bytes, \
+                 it has to end at least {} bytes after the start-of-packet marker",
+                START_OF_MESSAGE_SIZE - START_OF_PACKET_SIZE
+            ),
+            GeneratorError::StreamTooShort { len } => write!(
+                f,
+                "a stream of {len} bytes cannot contain the start-of-message marker"
+            ),
+        }
+    }
+}
+
+// The offsets are the numbers of bytes processed until the end of the first markers,
+// as in the puzzle. The bytes before a marker window are generated such that every
+// window repeats a byte, and the byte right before the start-of-packet marker is
+// repeated as its first byte, so that no window overlapping the marker qualifies.
+pub fn generate<R: Rng>(
+    alphabet: &[u8],
+    start_of_packet: usize,
+    start_of_message: usize,
+    len: usize,
+    rng: &mut R,
+) -> Result<Vec<u8>, GeneratorError> {
+    let mut alphabet = alphabet.to_vec();
+    alphabet.sort_unstable();
+    alphabet.dedup();
+
+    if alphabet.len() < START_OF_MESSAGE_SIZE {
+        return Err(GeneratorError::AlphabetTooSmall {
+            num_distinct: alphabet.len(),
+        });
+    }
+    if start_of_packet < START_OF_PACKET_SIZE {
+        return Err(GeneratorError::StartOfPacketTooEarly { start_of_packet });
+    }
+    if start_of_message < start_of_packet + START_OF_MESSAGE_SIZE - START_OF_PACKET_SIZE {
+        return Err(GeneratorError::StartOfMessageTooEarly { start_of_message });
+    }
+    if len < start_of_message {
+        return Err(GeneratorError::StreamTooShort { len });
+    }
+
+    let packet_start = start_of_packet - START_OF_PACKET_SIZE;
+    let message_start = start_of_message - START_OF_MESSAGE_SIZE;
+
+    let mut bytes = Vec::with_capacity(len);
+
+    push_without_marker(
+        &mut bytes,
+        packet_start,
+        START_OF_PACKET_SIZE,
+        &alphabet,
+        rng,
+    );
+    if packet_start > 0 {
+        bytes.push(bytes[packet_start - 1]);
+    }
+    push_distinct(&mut bytes, start_of_packet, packet_start, &alphabet, rng);
+
+    push_without_marker(
+        &mut bytes,
+        message_start,
+        START_OF_MESSAGE_SIZE,
+        &alphabet,
+        rng,
+    );
+    // The byte before the message marker is repeated as the first byte of the marker
+    // that is not part of the packet marker. The windows overlapping the start of the
+    // message marker contain both. If the markers start together, the repeated byte
+    // before the packet marker covers these windows already.
+    if message_start > packet_start {
+        bytes.push(bytes[message_start - 1]);
+    }
+    push_distinct(&mut bytes, start_of_message, message_start, &alphabet, rng);
+
+    while bytes.len() < len {
+        bytes.push(*alphabet.choose(rng).unwrap());
+    }
+
+    Ok(bytes)
+}
+
+// every complete window that ends with one of the new bytes repeats a byte
+fn push_without_marker<R: Rng>(
+    bytes: &mut Vec<u8>,
+    end: usize,
+    window_size: usize,
+    alphabet: &[u8],
+    rng: &mut R,
+) {
+    while bytes.len() < end {
+        let recent_bytes = &bytes[bytes.len().saturating_sub(window_size - 1)..];
+        let recent_bytes_are_distinct =
+            recent_bytes.iter().collect::<HashSet<_>>().len() == window_size - 1;
+
+        let byte = if recent_bytes_are_distinct {
+            *recent_bytes.choose(rng).unwrap()
+        } else {
+            *alphabet.choose(rng).unwrap()
+        };
+
+        bytes.push(byte);
+    }
+}
+
+// the bytes from window_start to end are all distinct
+fn push_distinct<R: Rng>(
+    bytes: &mut Vec<u8>,
+    end: usize,
+    window_start: usize,
+    alphabet: &[u8],
+    rng: &mut R,
+) {
+    while bytes.len() < end {
+        let unused_bytes: Vec<_> = alphabet
+            .iter()
+            .filter(|byte| !bytes[window_start..].contains(byte))
+            .collect();
+
+        bytes.push(**unused_bytes.choose(rng).unwrap());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::{generate, GeneratorError};
+    use crate::index_after_first_n_consecutive_distict_chars;
+
+    #[test]
+    fn reject_impossible_offsets() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let letters: Vec<_> = (b'a'..=b'z').collect();
+
+        assert_eq!(
+            generate(b"abcdefghijklmabc", 4, 14, 20, &mut rng),
+            Err(GeneratorError::AlphabetTooSmall { num_distinct: 13 })
+        );
+        assert_eq!(
+            generate(&letters, 3, 14, 20, &mut rng),
+            Err(GeneratorError::StartOfPacketTooEarly { start_of_packet: 3 })
+        );
+        assert_eq!(
+            generate(&letters, 7, 16, 20, &mut rng),
+            Err(GeneratorError::StartOfMessageTooEarly {
+                start_of_message: 16
+            })
+        );
+        assert_eq!(
+            generate(&letters, 7, 19, 18, &mut rng),
+            Err(GeneratorError::StreamTooShort { len: 18 })
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn markers_at_requested_offsets(
+            alphabet_size in 14..40u8,
+            start_of_packet in 4..60usize,
+            message_distance in 10..60usize,
+            num_trailing in 0..40usize,
+            seed in any::<u64>(),
+        ) {
+            let alphabet: Vec<_> = (0..alphabet_size).map(|offset| b'0' + offset).collect();
+            let start_of_message = start_of_packet + message_distance;
+            let len = start_of_message + num_trailing;
+
+            let mut rng = StdRng::seed_from_u64(seed);
+            let bytes = generate(&alphabet, start_of_packet, start_of_message, len, &mut rng).unwrap();
+
+            prop_assert_eq!(bytes.len(), len);
+            prop_assert!(bytes.iter().all(|byte| alphabet.contains(byte)));
+            prop_assert_eq!(index_after_first_n_consecutive_distict_chars(&bytes, 4), Ok(start_of_packet));
+            prop_assert_eq!(index_after_first_n_consecutive_distict_chars(&bytes, 14), Ok(start_of_message));
+        }
+    }
+}
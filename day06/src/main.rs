@@ -1,12 +1,178 @@
+mod analysis;
+mod bench;
+mod bitmask_detector;
+mod generator;
+mod marker_detector;
+mod symbol_counter;
+
 use hashbag::HashBag;
-use std::fs;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::hash::Hash;
+use std::{env, fs, io};
+
+use marker_detector::MarkerDetector;
+use symbol_counter::{ByteCounter, SymbolCounter};
 
 fn main() {
+    let args: Vec<_> = env::args().skip(1).collect();
+
+    // usage: bench [--max-bytes <number>] [--seed <number>], generates its own input
+    if args.first().map(String::as_str) == Some("bench") {
+        let mut max_num_bytes = 1 << 30;
+        let mut seed = 0;
+
+        let mut args = args[1..].iter();
+        while let Some(arg) = args.next() {
+            let value = args
+                .next()
+                .and_then(|value| value.parse().ok())
+                .unwrap_or_else(|| panic!("Expected a number after {arg}"));
+
+            match arg.as_str() {
+                "--max-bytes" => max_num_bytes = value,
+                "--seed" => seed = value,
+                _ => panic!("Unknown bench option {arg}"),
+            }
+        }
+
+        bench::run(max_num_bytes, seed as u64);
+        return;
+    }
+
+    // usage: generate <alphabet> <start-of-packet offset> <start-of-message offset>
+    //        [--len <number>] [--seed <number>], writes the datastream to stdout
+    if args.first().map(String::as_str) == Some("generate") {
+        let alphabet = args.get(1).expect("Expected an alphabet").as_bytes();
+        let [start_of_packet, start_of_message] = [2, 3].map(|arg_idx| {
+            args.get(arg_idx)
+                .and_then(|arg| arg.parse().ok())
+                .expect("Expected two marker offsets")
+        });
+        let mut len = start_of_message;
+        let mut seed = 0;
+
+        let mut args = args.iter().skip(4);
+        while let Some(arg) = args.next() {
+            let value = args
+                .next()
+                .and_then(|value| value.parse().ok())
+                .unwrap_or_else(|| panic!("Expected a number after {arg}"));
+
+            match arg.as_str() {
+                "--len" => len = value,
+                "--seed" => seed = value,
+                _ => panic!("Unknown generate option {arg}"),
+            }
+        }
+
+        let mut rng = StdRng::seed_from_u64(seed as u64);
+        let datastream =
+            generator::generate(alphabet, start_of_packet, start_of_message, len, &mut rng)
+                .unwrap_or_else(|error| panic!("{error}"));
+
+        // confirm the markers with the detector that solves the puzzle
+        assert_eq!(
+            index_after_first_n_consecutive_distict_chars(&datastream, 4),
+            Ok(start_of_packet)
+        );
+        assert_eq!(
+            index_after_first_n_consecutive_distict_chars(&datastream, 14),
+            Ok(start_of_message)
+        );
+
+        println!("{}", String::from_utf8_lossy(&datastream));
+        return;
+    }
+
+    // usage: stream [<marker size>...], reads the datastream from stdin
+    if args.first().map(String::as_str) == Some("stream") {
+        let window_sizes: Vec<usize> = if args.len() > 1 {
+            args[1..]
+                .iter()
+                .map(|arg| arg.parse().expect("Marker sizes should be numbers"))
+                .collect()
+        } else {
+            vec![4, 14]
+        };
+
+        let mut detector = MarkerDetector::<u8, ByteCounter>::new(&window_sizes);
+        detector
+            .feed_reader(io::stdin().lock(), |marker| {
+                println!(
+                    "Number of bytes processed before the first marker of {} distinct bytes: {}",
+                    marker.num_distinct, marker.end_offset
+                );
+            })
+            .unwrap();
+
+        if !detector.is_done() {
+            println!("The datastream ended before all markers were found");
+        }
+
+        return;
+    }
+
     let input = fs::read_to_string("input/day6.txt").unwrap();
 
+    // usage: symbols <chars | tokens | numbers> <marker size>
+    // tokens and numbers are separated by whitespace, the input does not need to be ASCII
+    if args.first().map(String::as_str) == Some("symbols") {
+        let num_consecutive = args
+            .get(2)
+            .and_then(|arg| arg.parse().ok())
+            .expect("Expected a marker size");
+
+        let first_marker_end = match args.get(1).map(String::as_str) {
+            Some("chars") => {
+                let chars: Vec<_> = input.chars().collect();
+                index_after_first_n_consecutive_distinct_symbols(&chars, num_consecutive)
+            }
+            Some("tokens") => {
+                let tokens: Vec<_> = input.split_whitespace().collect();
+                index_after_first_n_consecutive_distinct_symbols(&tokens, num_consecutive)
+            }
+            Some("numbers") => {
+                let numbers: Vec<u64> = input
+                    .split_whitespace()
+                    .map(|token| token.parse().expect("Expected only numbers"))
+                    .collect();
+                index_after_first_n_consecutive_distinct_symbols(&numbers, num_consecutive)
+            }
+            _ => panic!("Expected chars, tokens or numbers"),
+        }
+        .unwrap_or_else(|error| panic!("{error}"));
+
+        println!("Number of symbols processed before the first marker: {first_marker_end}");
+        return;
+    }
+
     assert!(input.is_ascii());
     let ascii_byte_input = input.into_bytes();
 
+    // usage: analyze <marker size>
+    if args.first().map(String::as_str) == Some("analyze") {
+        let num_consecutive = args
+            .get(1)
+            .and_then(|arg| arg.parse().ok())
+            .expect("Expected a marker size");
+
+        let end_offsets = analysis::all_marker_end_offsets(&ascii_byte_input, num_consecutive);
+        println!(
+            "Number of markers of {num_consecutive} distinct characters: {}",
+            end_offsets.len()
+        );
+        println!("Characters processed before each of them: {end_offsets:?}");
+
+        let longest_run = analysis::longest_distinct_run(&ascii_byte_input);
+        println!(
+            "Longest run of distinct characters: {} characters, starting after {} characters",
+            longest_run.len, longest_run.start
+        );
+
+        return;
+    }
+
     let first_start_of_packet =
         index_after_first_n_consecutive_distict_chars(&ascii_byte_input, 4).unwrap();
 
@@ -18,28 +184,107 @@ fn main() {
     println!("Number of character processed before the first start-of-message marker: {first_start_of_message}");
 }
 
-// ultra unnecessarily efficient implementation of this function
+// ultra unnecessarily efficient implementation of this function, without hashing the bytes
 fn index_after_first_n_consecutive_distict_chars(
     ascii_chars: &[u8],
     num_consecutive: usize,
 ) -> Result<usize, String> {
-    let (first_window, after_first_window) = ascii_chars.split_at(num_consecutive);
-    let mut current_chars: HashBag<_> = first_window.iter().collect();
+    index_after_first_n_consecutive_distinct(ascii_chars, num_consecutive, ByteCounter::new())
+}
+
+// for any other kind of symbols, e.g. chars, tokens or numeric IDs
+fn index_after_first_n_consecutive_distinct_symbols<T: Hash + Eq>(
+    symbols: &[T],
+    num_consecutive: usize,
+) -> Result<usize, String> {
+    index_after_first_n_consecutive_distinct(symbols, num_consecutive, HashBag::new())
+}
+
+// the counter has to be empty in the beginning
+fn index_after_first_n_consecutive_distinct<'a, T, C>(
+    symbols: &'a [T],
+    num_consecutive: usize,
+    mut current_symbols: C,
+) -> Result<usize, String>
+where
+    C: SymbolCounter<&'a T>,
+{
+    let error = || format!("slice does not contain {num_consecutive} distinct symbols");
+
+    if symbols.len() < num_consecutive {
+        return Err(error());
+    }
 
-    let window_border_iter = ascii_chars.iter().zip(after_first_window.iter());
+    let (first_window, after_first_window) = symbols.split_at(num_consecutive);
+    for symbol in first_window {
+        current_symbols.insert(symbol);
+    }
+
+    let window_border_iter = symbols.iter().zip(after_first_window.iter());
 
-    for (window_idx, (oldest_current_char, next_char)) in window_border_iter.enumerate() {
-        if current_chars.set_len() == num_consecutive {
+    for (window_idx, (oldest_current_symbol, next_symbol)) in window_border_iter.enumerate() {
+        if current_symbols.num_distinct() == num_consecutive {
             // offset of num_consecutive - 1 for the incomplete windows at the beginning
             // plus 1 for 0-based index
             return Ok(window_idx + num_consecutive);
         }
 
-        current_chars.remove(oldest_current_char);
-        current_chars.insert(next_char);
+        current_symbols.remove(&oldest_current_symbol);
+        current_symbols.insert(next_symbol);
+    }
+
+    // the last window is not checked in the loop
+    if current_symbols.num_distinct() == num_consecutive {
+        return Ok(symbols.len());
+    }
+
+    Err(error())
+}
+
+mod tests {
+    #[test]
+    fn markers_of_other_symbols() {
+        use super::index_after_first_n_consecutive_distinct_symbols as first_marker_end;
+
+        let chars: Vec<_> = "äöäüßéa".chars().collect();
+        assert_eq!(first_marker_end(&chars, 4), Ok(5));
+
+        let tokens: Vec<_> = "GET PUT GET GET POST PUT"
+            .split(' ')
+            .map(String::from)
+            .collect();
+        assert_eq!(first_marker_end(&tokens, 3), Ok(6));
+
+        let sensor_ids = [7u64, 7, 1_000_000_007, 7, 42];
+        assert_eq!(first_marker_end(&sensor_ids, 3), Ok(5));
+        assert!(first_marker_end(&sensor_ids, 4).is_err());
     }
 
-    Err(format!(
-        "slice does not contain {num_consecutive} distinct chars"
-    ))
+    #[test]
+    fn byte_fast_path_agrees() {
+        use super::{
+            index_after_first_n_consecutive_distict_chars,
+            index_after_first_n_consecutive_distinct_symbols,
+        };
+
+        for datastream in [
+            "bvwbjplbgvbhsrlpgdmjqwftvncz",
+            "nppdvjthqldpwncqszvftbrmjlhg",
+            "nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg",
+            "zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw",
+        ] {
+            for num_consecutive in [4, 14] {
+                assert_eq!(
+                    index_after_first_n_consecutive_distict_chars(
+                        datastream.as_bytes(),
+                        num_consecutive
+                    ),
+                    index_after_first_n_consecutive_distinct_symbols(
+                        datastream.as_bytes(),
+                        num_consecutive
+                    )
+                );
+            }
+        }
+    }
 }
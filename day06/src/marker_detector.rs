@@ -0,0 +1,176 @@
+use std::collections::VecDeque;
+use std::io::{self, Read};
+
+use hashbag::HashBag;
+
+use crate::symbol_counter::SymbolCounter;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Marker {
+    pub num_distinct: usize,
+    // number of symbols processed until the end of the marker
+    pub end_offset: usize,
+}
+
+struct Window<T, C> {
+    num_distinct: usize,
+    recent_symbols: VecDeque<T>,
+    current_symbols: C,
+    found: bool,
+}
+
+// Finds the first window of n distinct symbols for several n at once, without
+// needing the whole datastream in memory. For bytes, use ByteCounter as C.
+pub struct MarkerDetector<T, C = HashBag<T>> {
+    windows: Vec<Window<T, C>>,
+    num_processed: usize,
+}
+
+impl<T: Clone, C: SymbolCounter<T> + Default> MarkerDetector<T, C> {
+    pub fn new(window_sizes: &[usize]) -> Self {
+        let windows = window_sizes
+            .iter()
+            .map(|&num_distinct| {
+                assert!(num_distinct > 0, "Markers consist of at least one symbol");
+
+                Window {
+                    num_distinct,
+                    recent_symbols: VecDeque::with_capacity(num_distinct + 1),
+                    current_symbols: C::default(),
+                    found: false,
+                }
+            })
+            .collect();
+
+        MarkerDetector {
+            windows,
+            num_processed: 0,
+        }
+    }
+
+    // all markers were found, so there is no need to feed more data
+    pub fn is_done(&self) -> bool {
+        self.windows.iter().all(|window| window.found)
+    }
+
+    // returns the markers that were completed by this chunk, in the order of their end
+    pub fn feed(&mut self, chunk: &[T]) -> Vec<Marker> {
+        let mut completed_markers = Vec::new();
+
+        for symbol in chunk {
+            if self.is_done() {
+                break;
+            }
+
+            self.num_processed += 1;
+
+            for window in self.windows.iter_mut().filter(|window| !window.found) {
+                window.recent_symbols.push_back(symbol.clone());
+                window.current_symbols.insert(symbol.clone());
+
+                if window.recent_symbols.len() > window.num_distinct {
+                    let oldest_symbol = window.recent_symbols.pop_front().unwrap();
+                    window.current_symbols.remove(&oldest_symbol);
+                }
+
+                if window.current_symbols.num_distinct() == window.num_distinct {
+                    window.found = true;
+                    completed_markers.push(Marker {
+                        num_distinct: window.num_distinct,
+                        end_offset: self.num_processed,
+                    });
+                }
+            }
+        }
+
+        completed_markers
+    }
+}
+
+impl<C: SymbolCounter<u8> + Default> MarkerDetector<u8, C> {
+    // Reads until all markers are found or the reader is exhausted, and calls
+    // on_marker as soon as a marker is completed.
+    pub fn feed_reader<R, F>(&mut self, mut reader: R, mut on_marker: F) -> io::Result<()>
+    where
+        R: Read,
+        F: FnMut(Marker),
+    {
+        let mut buffer = [0; 8192];
+
+        while !self.is_done() {
+            let num_read = match reader.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(num_read) => num_read,
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+                Err(error) => return Err(error),
+            };
+
+            for marker in self.feed(&buffer[..num_read]) {
+                on_marker(marker);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+mod tests {
+    #[test]
+    fn markers_across_chunks() {
+        use super::{Marker, MarkerDetector};
+        use crate::symbol_counter::ByteCounter;
+
+        let datastream = b"mjqjpqmgbljsphdztnvjfqwrcgsmlb";
+
+        for chunk_size in [1, 3, 7, datastream.len()] {
+            let mut detector = MarkerDetector::<u8, ByteCounter>::new(&[4, 14]);
+
+            let markers: Vec<_> = datastream
+                .chunks(chunk_size)
+                .flat_map(|chunk| detector.feed(chunk))
+                .collect();
+
+            assert_eq!(
+                markers,
+                [
+                    Marker {
+                        num_distinct: 4,
+                        end_offset: 7
+                    },
+                    Marker {
+                        num_distinct: 14,
+                        end_offset: 19
+                    }
+                ]
+            );
+            assert!(detector.is_done());
+        }
+    }
+
+    #[test]
+    fn markers_in_a_token_stream() {
+        use super::{Marker, MarkerDetector};
+
+        let tokens: Vec<_> = "a b a c a b c d".split(' ').map(String::from).collect();
+        let mut detector = MarkerDetector::<String>::new(&[3, 4]);
+
+        let markers: Vec<_> = tokens
+            .chunks(2)
+            .flat_map(|chunk| detector.feed(chunk))
+            .collect();
+
+        assert_eq!(
+            markers,
+            [
+                Marker {
+                    num_distinct: 3,
+                    end_offset: 4
+                },
+                Marker {
+                    num_distinct: 4,
+                    end_offset: 8
+                }
+            ]
+        );
+    }
+}
@@ -0,0 +1,85 @@
+use std::hash::Hash;
+
+use hashbag::HashBag;
+
+// the multiset of symbols in a sliding window, only the number of distinct symbols matters
+pub trait SymbolCounter<T> {
+    fn insert(&mut self, symbol: T);
+    fn remove(&mut self, symbol: &T);
+    fn num_distinct(&self) -> usize;
+}
+
+impl<T: Hash + Eq> SymbolCounter<T> for HashBag<T> {
+    fn insert(&mut self, symbol: T) {
+        HashBag::insert(self, symbol);
+    }
+
+    fn remove(&mut self, symbol: &T) {
+        HashBag::remove(self, symbol);
+    }
+
+    fn num_distinct(&self) -> usize {
+        self.set_len()
+    }
+}
+
+// fast path for bytes, a plain array instead of hashing every byte
+pub struct ByteCounter {
+    counts: [usize; 256],
+    num_distinct: usize,
+}
+
+impl ByteCounter {
+    pub fn new() -> Self {
+        ByteCounter {
+            counts: [0; 256],
+            num_distinct: 0,
+        }
+    }
+}
+
+impl Default for ByteCounter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SymbolCounter<u8> for ByteCounter {
+    fn insert(&mut self, symbol: u8) {
+        let count = &mut self.counts[symbol as usize];
+        if *count == 0 {
+            self.num_distinct += 1;
+        }
+        *count += 1;
+    }
+
+    fn remove(&mut self, symbol: &u8) {
+        let count = &mut self.counts[*symbol as usize];
+        if *count == 0 {
+            return;
+        }
+        *count -= 1;
+        if *count == 0 {
+            self.num_distinct -= 1;
+        }
+    }
+
+    fn num_distinct(&self) -> usize {
+        self.num_distinct
+    }
+}
+
+// for windows over borrowed byte slices
+impl SymbolCounter<&u8> for ByteCounter {
+    fn insert(&mut self, symbol: &u8) {
+        SymbolCounter::<u8>::insert(self, *symbol);
+    }
+
+    fn remove(&mut self, symbol: &&u8) {
+        SymbolCounter::<u8>::remove(self, *symbol);
+    }
+
+    fn num_distinct(&self) -> usize {
+        self.num_distinct
+    }
+}
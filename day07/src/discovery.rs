@@ -1,8 +1,94 @@
+use std::collections::HashSet;
+
 use crate::filesystem_model::{Command, FilesystemItem, FilesystemTree, RelativeDirectory};
 
+// Something in the history that does not fit to what was discovered before. The command
+// numbers are 1-based. Directories are kept as ids, their paths are only built when the
+// diagnostic is described, so deep trees do not need quadratic space.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Diagnostic {
+    ConflictingListing {
+        command_number: usize,
+        directory_id: usize,
+        known_item: FilesystemItem,
+        listed_item: FilesystemItem,
+    },
+    MissingFromListing {
+        command_number: usize,
+        directory_id: usize,
+        known_item: FilesystemItem,
+    },
+    // the id is the one of the placeholder that was created for the directory
+    UnlistedDirectoryEntered {
+        command_number: usize,
+        directory_id: usize,
+    },
+    NotADirectory {
+        command_number: usize,
+        directory_id: usize,
+        name: String,
+    },
+    ParentOfRoot {
+        command_number: usize,
+    },
+}
+
+impl Diagnostic {
+    // the ids refer to the tree of the discoverer, before any subtrees are removed
+    pub fn describe(&self, tree: &FilesystemTree) -> String {
+        use Diagnostic::*;
+
+        match self {
+            ConflictingListing {
+                command_number,
+                directory_id,
+                known_item,
+                listed_item,
+            } => format!(
+                "command {command_number}: {} lists {listed_item}, \
+                 but it was {known_item} before, keeping the first one",
+                tree.path_of(*directory_id)
+            ),
+            MissingFromListing {
+                command_number,
+                directory_id,
+                known_item,
+            } => format!(
+                "command {command_number}: {} does not list {known_item} anymore",
+                tree.path_of(*directory_id)
+            ),
+            UnlistedDirectoryEntered {
+                command_number,
+                directory_id,
+            } => format!(
+                "command {command_number}: entered {} before it was listed",
+                tree.path_of(*directory_id)
+            ),
+            NotADirectory {
+                command_number,
+                directory_id,
+                name,
+            } => {
+                let directory_path = tree.path_of(*directory_id);
+                let separator = if directory_path == "/" { "" } else { "/" };
+                format!(
+                    "command {command_number}: cannot enter {directory_path}{separator}{name}, \
+                     it is a file"
+                )
+            }
+            ParentOfRoot { command_number } => {
+                format!("command {command_number}: cannot leave the root, staying there")
+            }
+        }
+    }
+}
+
 pub struct FilesystemDiscoverer {
     current_directory_id: usize,
     tree: FilesystemTree,
+    listed_directory_ids: HashSet<usize>,
+    num_applied_commands: usize,
+    diagnostics: Vec<Diagnostic>,
 }
 
 impl FilesystemDiscoverer {
@@ -10,10 +96,15 @@ impl FilesystemDiscoverer {
         FilesystemDiscoverer {
             current_directory_id: 0,
             tree: FilesystemTree::with_root(),
+            listed_directory_ids: HashSet::new(),
+            num_applied_commands: 0,
+            diagnostics: Vec::new(),
         }
     }
 
     pub fn apply_command(&mut self, command: &Command) {
+        self.num_applied_commands += 1;
+
         match command {
             Command::ChangeDirectory { target } => {
                 self.change_directory(target);
@@ -24,46 +115,217 @@ impl FilesystemDiscoverer {
         }
     }
 
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    // the tree discovered so far, the diagnostics refer to its ids
+    pub fn tree(&self) -> &FilesystemTree {
+        &self.tree
+    }
+
+    // the directories that were never listed, the sizes of them and their parents are lower bounds
+    pub fn unexplored_directory_ids(&self) -> Vec<usize> {
+        self.tree
+            .nodes()
+            .iter()
+            .filter(|node| matches!(node.item, FilesystemItem::Directory { .. }))
+            .map(|node| node.id)
+            .filter(|id| !self.listed_directory_ids.contains(id))
+            .collect()
+    }
+
     pub fn finish(self) -> FilesystemTree {
         self.tree
     }
 
-    // return id of new directory
+    // Entering a directory that was never listed creates a placeholder for it. Invalid
+    // targets leave the current directory unchanged.
     fn change_directory(&mut self, target: &RelativeDirectory) {
-        let nodes = &self.tree.nodes();
-        let current_directory = &nodes[self.current_directory_id];
+        if let Some(target_id) = self
+            .tree
+            .relative_directory_id(self.current_directory_id, target)
+        {
+            self.current_directory_id = target_id;
+            return;
+        }
 
-        let next_node = match target {
-            RelativeDirectory::Child { name } => {
-                let &child_id = current_directory
-                    .child_ids
-                    .get(name)
-                    .expect("Should not call cd on undiscovered children");
+        let command_number = self.num_applied_commands;
 
-                &nodes[child_id]
+        match target {
+            RelativeDirectory::Child { name }
+                if self.tree.has_child_name(self.current_directory_id, name) =>
+            {
+                self.diagnostics.push(Diagnostic::NotADirectory {
+                    command_number,
+                    directory_id: self.current_directory_id,
+                    name: name.clone(),
+                });
             }
-            RelativeDirectory::Parent => {
-                let parent_id = current_directory
-                    .parent_id
-                    .expect("Should not call cd .. at root");
+            RelativeDirectory::Child { name } => {
+                self.tree.insert_child(
+                    self.current_directory_id,
+                    FilesystemItem::Directory { name: name.clone() },
+                );
+                self.current_directory_id = self.tree.len() - 1;
 
-                &nodes[parent_id]
+                self.diagnostics.push(Diagnostic::UnlistedDirectoryEntered {
+                    command_number,
+                    directory_id: self.current_directory_id,
+                });
             }
-            RelativeDirectory::Root => self.tree.root(),
-        };
-
-        self.current_directory_id = next_node.id;
+            RelativeDirectory::Parent | RelativeDirectory::Root => {
+                self.diagnostics
+                    .push(Diagnostic::ParentOfRoot { command_number });
+            }
+        }
     }
 
     fn update_directory(&mut self, list_items: &[FilesystemItem]) {
+        let command_number = self.num_applied_commands;
+        let current_directory = &self.tree.nodes()[self.current_directory_id];
+
+        if self
+            .listed_directory_ids
+            .contains(&self.current_directory_id)
+        {
+            let mut known_child_ids: Vec<_> =
+                current_directory.child_ids.values().copied().collect();
+            known_child_ids.sort_unstable();
+
+            for child_id in known_child_ids {
+                let known_item = &self.tree.nodes()[child_id].item;
+                if !list_items
+                    .iter()
+                    .any(|item| item.name() == known_item.name())
+                {
+                    self.diagnostics.push(Diagnostic::MissingFromListing {
+                        command_number,
+                        directory_id: self.current_directory_id,
+                        known_item: known_item.clone(),
+                    });
+                }
+            }
+        }
+
         for item in list_items {
-            if !self
-                .tree
-                .has_child_name(self.current_directory_id, item.name())
-            {
-                self.tree
-                    .insert_child(self.current_directory_id, item.clone());
+            let current_directory = &self.tree.nodes()[self.current_directory_id];
+
+            match current_directory.child_ids.get(item.name()) {
+                None => self
+                    .tree
+                    .insert_child(self.current_directory_id, item.clone()),
+                Some(&child_id) if self.tree.nodes()[child_id].item != *item => {
+                    self.diagnostics.push(Diagnostic::ConflictingListing {
+                        command_number,
+                        directory_id: self.current_directory_id,
+                        known_item: self.tree.nodes()[child_id].item.clone(),
+                        listed_item: item.clone(),
+                    });
+                }
+                Some(_) => {}
             }
         }
+
+        self.listed_directory_ids.insert(self.current_directory_id);
+    }
+}
+
+// the example of the puzzle, shared by the tests
+#[cfg(test)]
+pub const EXAMPLE_TRANSCRIPT: &str = "$ cd /\n$ ls\ndir a\n14848514 b.txt\n8504156 c.dat\ndir d\n\
+                                      $ cd a\n$ ls\ndir e\n29116 f\n2557 g\n62596 h.lst\n\
+                                      $ cd e\n$ ls\n584 i\n$ cd ..\n$ cd ..\n$ cd d\n$ ls\n\
+                                      4060174 j\n8033020 d.log\n5626152 d.ext\n7214296 k\n";
+
+#[cfg(test)]
+pub fn discover(transcript: &str) -> FilesystemTree {
+    let mut discoverer = FilesystemDiscoverer::start_at_root();
+    for command in crate::input::parse(transcript) {
+        discoverer.apply_command(&command);
+    }
+    discoverer.finish()
+}
+
+#[cfg(test)]
+pub fn example_tree() -> FilesystemTree {
+    discover(EXAMPLE_TRANSCRIPT)
+}
+
+mod tests {
+    #[test]
+    fn inconsistent_history() {
+        use super::{Diagnostic, FilesystemDiscoverer};
+        use crate::filesystem_model::FilesystemItem;
+        use crate::input;
+
+        let history = "$ cd /\n$ ls\ndir a\n10 b\n\
+                       $ cd x\n$ cd ..\n$ cd b\n$ cd ..\n\
+                       $ ls\n20 b\n10 a\n";
+
+        let mut discoverer = FilesystemDiscoverer::start_at_root();
+        for command in input::parse(history) {
+            discoverer.apply_command(&command);
+        }
+
+        assert_eq!(
+            discoverer.diagnostics(),
+            [
+                Diagnostic::UnlistedDirectoryEntered {
+                    command_number: 3,
+                    directory_id: 3
+                },
+                Diagnostic::NotADirectory {
+                    command_number: 5,
+                    directory_id: 0,
+                    name: String::from("b")
+                },
+                Diagnostic::ParentOfRoot { command_number: 6 },
+                Diagnostic::MissingFromListing {
+                    command_number: 7,
+                    directory_id: 0,
+                    known_item: FilesystemItem::Directory {
+                        name: String::from("x")
+                    }
+                },
+                Diagnostic::ConflictingListing {
+                    command_number: 7,
+                    directory_id: 0,
+                    known_item: FilesystemItem::File {
+                        name: String::from("b"),
+                        size: 10
+                    },
+                    listed_item: FilesystemItem::File {
+                        name: String::from("b"),
+                        size: 20
+                    }
+                },
+                Diagnostic::ConflictingListing {
+                    command_number: 7,
+                    directory_id: 0,
+                    known_item: FilesystemItem::Directory {
+                        name: String::from("a")
+                    },
+                    listed_item: FilesystemItem::File {
+                        name: String::from("a"),
+                        size: 10
+                    }
+                },
+            ]
+        );
+
+        let descriptions: Vec<_> = discoverer
+            .diagnostics()
+            .iter()
+            .map(|diagnostic| diagnostic.describe(discoverer.tree()))
+            .collect();
+        assert_eq!(
+            descriptions[0],
+            "command 3: entered /x before it was listed"
+        );
+        assert_eq!(descriptions[1], "command 5: cannot enter /b, it is a file");
+
+        // a and the placeholder x
+        assert_eq!(discoverer.unexplored_directory_ids(), [1, 3]);
     }
 }
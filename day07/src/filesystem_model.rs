@@ -8,6 +8,19 @@ pub enum RelativeDirectory {
     Root,
 }
 
+// same meaning as the argument of cd in the input
+impl From<&str> for RelativeDirectory {
+    fn from(argument: &str) -> Self {
+        match argument {
+            ".." => RelativeDirectory::Parent,
+            "/" => RelativeDirectory::Root,
+            name => RelativeDirectory::Child {
+                name: name.to_owned(),
+            },
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum FilesystemItem {
     File { name: String, size: usize },
@@ -47,9 +60,12 @@ pub struct FilesystemNode {
     pub item: FilesystemItem,
     pub parent_id: Option<usize>,
     pub child_ids: HashMap<String, usize>,
+    // recursive for directories, kept up to date by the tree
+    pub size: usize,
 }
 
-// does not support node deletion
+// The ids are the indices of the nodes, removing a subtree renumbers the nodes after it.
+// All traversals use explicit stacks, so arbitrarily deep trees are fine.
 pub struct FilesystemTree {
     nodes: Vec<FilesystemNode>,
 }
@@ -63,6 +79,7 @@ impl FilesystemTree {
             },
             parent_id: None,
             child_ids: HashMap::new(),
+            size: 0,
         };
         FilesystemTree { nodes: vec![root] }
     }
@@ -82,6 +99,27 @@ impl FilesystemTree {
     pub fn has_child_name(&self, parent_id: usize, child_name: &String) -> bool {
         self.nodes[parent_id].child_ids.contains_key(child_name)
     }
+
+    // None if the target does not exist or is not a directory
+    pub fn relative_directory_id(
+        &self,
+        current_directory_id: usize,
+        target: &RelativeDirectory,
+    ) -> Option<usize> {
+        let current_directory = &self.nodes[current_directory_id];
+
+        let target_id = match target {
+            RelativeDirectory::Child { name } => *current_directory.child_ids.get(name)?,
+            RelativeDirectory::Parent => current_directory.parent_id?,
+            RelativeDirectory::Root => self.root().id,
+        };
+
+        match self.nodes[target_id].item {
+            FilesystemItem::Directory { .. } => Some(target_id),
+            FilesystemItem::File { .. } => None,
+        }
+    }
+
     pub fn insert_child(&mut self, parent_id: usize, child_item: FilesystemItem) {
         let new_id = self.len();
 
@@ -89,76 +127,169 @@ impl FilesystemTree {
             .child_ids
             .insert(child_item.name().clone(), new_id);
 
+        let size = match child_item {
+            FilesystemItem::File { size, .. } => size,
+            FilesystemItem::Directory { .. } => 0,
+        };
+
         let new_node = FilesystemNode {
             id: new_id,
             item: child_item,
             parent_id: Some(parent_id),
             child_ids: HashMap::new(),
+            size,
         };
 
         self.nodes.push(new_node);
+        self.add_to_ancestor_sizes(new_id, size as isize);
     }
 
-    pub fn visit_items<F>(&self, mut f: F)
-    where
-        F: FnMut(&FilesystemItem, usize),
-    {
-        self.visit_node(&mut f, self.root(), 0);
+    // walks the parent chain, which is only necessary for non-empty nodes
+    fn add_to_ancestor_sizes(&mut self, node_id: usize, size_change: isize) {
+        if size_change == 0 {
+            return;
+        }
+
+        let mut current_id = node_id;
+        while let Some(parent_id) = self.nodes[current_id].parent_id {
+            let parent = &mut self.nodes[parent_id];
+            parent.size = parent.size.wrapping_add_signed(size_change);
+            current_id = parent_id;
+        }
     }
 
-    fn visit_node<F>(&self, f: &mut F, current_node: &FilesystemNode, depth: usize)
+    // Removes the node with everything below it and returns the number of removed nodes.
+    // The remaining nodes keep their order, so ids smaller than node_id stay valid.
+    pub fn remove_subtree(&mut self, node_id: usize) -> usize {
+        let parent_id = self.nodes[node_id]
+            .parent_id
+            .expect("Should not remove the root");
+
+        let name = self.nodes[node_id].item.name().clone();
+        self.nodes[parent_id].child_ids.remove(&name);
+        self.add_to_ancestor_sizes(node_id, -(self.nodes[node_id].size as isize));
+
+        let mut is_removed = vec![false; self.len()];
+        let mut pending_ids = vec![node_id];
+        while let Some(removed_id) = pending_ids.pop() {
+            is_removed[removed_id] = true;
+            pending_ids.extend(self.nodes[removed_id].child_ids.values());
+        }
+
+        let mut new_ids = Vec::with_capacity(self.len());
+        let mut num_kept = 0;
+        for &removed in &is_removed {
+            new_ids.push(num_kept);
+            if !removed {
+                num_kept += 1;
+            }
+        }
+
+        let num_removed = self.len() - num_kept;
+        let old_nodes = std::mem::take(&mut self.nodes);
+
+        self.nodes = old_nodes
+            .into_iter()
+            .filter(|node| !is_removed[node.id])
+            .map(|mut node| {
+                node.id = new_ids[node.id];
+                node.parent_id = node.parent_id.map(|parent_id| new_ids[parent_id]);
+                for child_id in node.child_ids.values_mut() {
+                    *child_id = new_ids[*child_id];
+                }
+                node
+            })
+            .collect();
+
+        num_removed
+    }
+
+    // children are visited in the order of their names, the depth is relative to the start
+    pub fn visit_nodes_from<'a, F>(&'a self, start_id: usize, mut f: F)
     where
-        F: FnMut(&FilesystemItem, usize),
+        F: FnMut(&'a FilesystemNode, usize),
     {
-        f(&current_node.item, depth);
+        let mut pending_nodes = vec![(start_id, 0)];
+
+        while let Some((node_id, depth)) = pending_nodes.pop() {
+            let current_node = &self.nodes[node_id];
+            f(current_node, depth);
 
-        let mut child_ids: Vec<_> = current_node.child_ids.values().copied().collect();
-        child_ids.sort_by_key(|&id| self.nodes[id].item.name());
+            let mut child_ids: Vec<_> = current_node.child_ids.values().copied().collect();
+            child_ids.sort_by_key(|&id| self.nodes[id].item.name());
 
-        for child_id in child_ids {
-            self.visit_node(f, &self.nodes[child_id], depth + 1);
+            // the first child has to be on top of the stack
+            pending_nodes.extend(child_ids.into_iter().rev().map(|id| (id, depth + 1)));
         }
     }
 
     #[allow(unused)]
     pub fn format_to_string(&self) -> String {
+        self.format_subtree_to_string(self.root().id)
+    }
+
+    pub fn format_subtree_to_string(&self, start_id: usize) -> String {
         let mut s = String::new();
 
-        self.visit_items(|item, depth| {
+        self.visit_nodes_from(start_id, |node, depth| {
             let padding = depth * 4;
-            writeln!(s, "{:padding$}- {item}", "").unwrap();
+            writeln!(s, "{:padding$}- {}", "", node.item).unwrap();
         });
 
         s
     }
 
-    pub fn recursive_directory_sizes(&self) -> HashMap<usize, usize> {
-        let mut directory_sizes = HashMap::new();
-
-        self.node_and_subdirectory_sizes(self.root(), &mut directory_sizes);
+    // O(1), because the sizes are updated on every change
+    pub fn size(&self, node_id: usize) -> usize {
+        self.nodes[node_id].size
+    }
 
-        directory_sizes
+    pub fn recursive_directory_sizes(&self) -> HashMap<usize, usize> {
+        self.nodes
+            .iter()
+            .filter(|node| matches!(node.item, FilesystemItem::Directory { .. }))
+            .map(|node| (node.id, node.size))
+            .collect()
     }
+}
 
-    fn node_and_subdirectory_sizes(
-        &self,
-        current_node: &FilesystemNode,
-        directory_sizes: &mut HashMap<usize, usize>,
-    ) -> usize {
-        match &current_node.item {
-            FilesystemItem::File { name: _, size } => *size,
-            FilesystemItem::Directory { name: _ } => {
-                let mut total_size = 0;
-
-                for &child_id in current_node.child_ids.values() {
-                    let child_node = &self.nodes[child_id];
-                    total_size += self.node_and_subdirectory_sizes(child_node, directory_sizes);
-                }
+mod tests {
+    #[test]
+    fn very_deep_tree() {
+        use crate::{discovery::discover, real_filesystem};
 
-                directory_sizes.insert(current_node.id, total_size);
+        const DEPTH: usize = 100_000;
 
-                total_size
-            }
+        let mut transcript = String::from("$ cd /\n");
+        for _ in 0..DEPTH {
+            transcript.push_str("$ ls\ndir d\n$ cd d\n");
         }
+        transcript.push_str("$ ls\n7 f\n");
+
+        let mut tree = discover(&transcript);
+
+        assert_eq!(tree.len(), DEPTH + 2);
+        assert!(tree
+            .recursive_directory_sizes()
+            .values()
+            .all(|&size| size == 7));
+
+        let mut max_depth = 0;
+        tree.visit_nodes_from(tree.root().id, |_, depth| max_depth = max_depth.max(depth));
+        assert_eq!(max_depth, DEPTH + 1);
+
+        assert_eq!(
+            real_filesystem::transcript(&tree).len(),
+            transcript.len() + DEPTH * "$ cd ..\n".len()
+        );
+
+        let found = tree.find("**/f");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].size, 7);
+        assert_eq!(tree.find("d/d/*").len(), 1);
+
+        assert_eq!(tree.remove_subtree(DEPTH / 2), DEPTH / 2 + 2);
+        assert_eq!(tree.root().size, 0);
+        assert_eq!(tree.size(DEPTH / 2 - 1), 0);
     }
 }
@@ -0,0 +1,269 @@
+use std::fmt::Display;
+
+use serde::Deserialize;
+
+use crate::filesystem_model::{FilesystemItem, FilesystemTree};
+
+// Directories are {"type": "directory", "name": ..., "children": [...]} and files are
+// {"type": "file", "name": ..., "size": ...}.
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum JsonNode {
+    Directory {
+        name: String,
+        children: Vec<JsonNode>,
+    },
+    File {
+        name: String,
+        size: usize,
+    },
+}
+
+#[derive(Debug)]
+pub enum JsonError {
+    Syntax(serde_json::Error),
+    RootIsNotADirectory,
+    DuplicateName {
+        directory_name: String,
+        name: String,
+    },
+}
+
+impl Display for JsonError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JsonError::Syntax(error) => write!(f, "invalid JSON tree: {error}"),
+            JsonError::RootIsNotADirectory => write!(f, "the root of the tree is not a directory"),
+            JsonError::DuplicateName {
+                directory_name,
+                name,
+            } => write!(f, "directory {directory_name} contains {name} twice"),
+        }
+    }
+}
+
+// The same layout as serde_json's pretty printer, children are sorted by name. Written
+// by hand instead of through serde, whose serializer recurses and would overflow the
+// stack on deep trees.
+pub fn export(tree: &FilesystemTree) -> String {
+    write_json(tree, true)
+}
+
+// Without any whitespace, pretty printing needs quadratic space for deep trees because
+// of the indentation.
+pub fn export_compact(tree: &FilesystemTree) -> String {
+    write_json(tree, false)
+}
+
+fn write_json(tree: &FilesystemTree, is_pretty: bool) -> String {
+    enum Pending {
+        Node {
+            id: usize,
+            level: usize,
+            is_first: bool,
+        },
+        EndOfChildren {
+            level: usize,
+        },
+    }
+
+    let mut s = String::new();
+    let key_separator = if is_pretty { ": " } else { ":" };
+    let line_break = |s: &mut String, indent: usize| {
+        if is_pretty {
+            s.push('\n');
+            s.extend(std::iter::repeat_n(' ', indent));
+        }
+    };
+
+    let mut pending = vec![Pending::Node {
+        id: tree.root().id,
+        level: 0,
+        is_first: true,
+    }];
+
+    while let Some(next) = pending.pop() {
+        match next {
+            Pending::Node {
+                id,
+                level,
+                is_first,
+            } => {
+                let node = &tree.nodes()[id];
+                let (indent, field_indent) = (4 * level, 4 * level + 2);
+
+                if !is_first {
+                    s.push(',');
+                }
+                if id != tree.root().id {
+                    line_break(&mut s, indent);
+                }
+                s.push('{');
+
+                let (type_name, size) = match node.item {
+                    FilesystemItem::File { size, .. } => ("file", Some(size)),
+                    FilesystemItem::Directory { .. } => ("directory", None),
+                };
+
+                line_break(&mut s, field_indent);
+                s.push_str(&format!("\"type\"{key_separator}\"{type_name}\","));
+                line_break(&mut s, field_indent);
+                let name = serde_json::to_string(node.item.name()).unwrap();
+                s.push_str(&format!("\"name\"{key_separator}{name},"));
+                line_break(&mut s, field_indent);
+
+                if let Some(size) = size {
+                    s.push_str(&format!("\"size\"{key_separator}{size}"));
+                    line_break(&mut s, indent);
+                    s.push('}');
+                    continue;
+                }
+
+                let mut child_ids: Vec<_> = node.child_ids.values().copied().collect();
+                child_ids.sort_by_key(|&id| tree.nodes()[id].item.name());
+
+                if child_ids.is_empty() {
+                    s.push_str(&format!("\"children\"{key_separator}[]"));
+                    line_break(&mut s, indent);
+                    s.push('}');
+                } else {
+                    s.push_str(&format!("\"children\"{key_separator}["));
+                    pending.push(Pending::EndOfChildren { level });
+                    pending.extend(child_ids.iter().enumerate().rev().map(
+                        |(child_idx, &child_id)| Pending::Node {
+                            id: child_id,
+                            level: level + 1,
+                            is_first: child_idx == 0,
+                        },
+                    ));
+                }
+            }
+            Pending::EndOfChildren { level } => {
+                line_break(&mut s, 4 * level + 2);
+                s.push(']');
+                line_break(&mut s, 4 * level);
+                s.push('}');
+            }
+        }
+    }
+
+    s
+}
+
+// serde_json rejects more than 128 levels of nesting with a syntax error. Every directory
+// takes two of them, its object and its children array, so trees with nodes more than
+// 63 levels below the root cannot be imported.
+pub fn import(json: &str) -> Result<FilesystemTree, JsonError> {
+    let root: JsonNode = serde_json::from_str(json).map_err(JsonError::Syntax)?;
+
+    let JsonNode::Directory { children, .. } = root else {
+        return Err(JsonError::RootIsNotADirectory);
+    };
+
+    let mut tree = FilesystemTree::with_root();
+    let mut pending_children = vec![(tree.root().id, children.into_iter())];
+
+    while let Some((parent_id, children)) = pending_children.last_mut() {
+        let parent_id = *parent_id;
+        let Some(child) = children.next() else {
+            pending_children.pop();
+            continue;
+        };
+
+        let (item, grandchildren) = match child {
+            JsonNode::File { name, size } => (FilesystemItem::File { name, size }, Vec::new()),
+            JsonNode::Directory { name, children } => {
+                (FilesystemItem::Directory { name }, children)
+            }
+        };
+
+        if tree.has_child_name(parent_id, item.name()) {
+            return Err(JsonError::DuplicateName {
+                directory_name: tree.nodes()[parent_id].item.name().clone(),
+                name: item.name().clone(),
+            });
+        }
+
+        tree.insert_child(parent_id, item);
+        pending_children.push((tree.len() - 1, grandchildren.into_iter()));
+    }
+
+    Ok(tree)
+}
+
+mod tests {
+    #[test]
+    fn round_trip_keeps_sizes() {
+        use super::{export, import};
+        use crate::discovery::example_tree;
+
+        let tree = example_tree();
+
+        let json = export(&tree);
+        let loaded_tree = import(&json).unwrap();
+
+        let sorted_sizes = |tree: &crate::filesystem_model::FilesystemTree| {
+            let mut sizes: Vec<_> = tree.recursive_directory_sizes().into_values().collect();
+            sizes.sort_unstable();
+            sizes
+        };
+
+        assert_eq!(sorted_sizes(&loaded_tree), sorted_sizes(&tree));
+        assert_eq!(loaded_tree.recursive_directory_sizes()[&0], 48381165);
+        assert_eq!(export(&loaded_tree), json);
+
+        assert!(import(r#"{"type": "file", "name": "/", "size": 1}"#).is_err());
+        assert!(import(
+            r#"{"type": "directory", "name": "/", "children": [
+                {"type": "file", "name": "x", "size": 1},
+                {"type": "directory", "name": "x", "children": []}
+            ]}"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn deep_trees() {
+        use super::{export, export_compact, import, JsonError};
+        use crate::discovery::discover;
+
+        // the limit documented at import
+        const MAX_IMPORT_DEPTH: usize = 63;
+
+        let nested_transcript = |depth: usize| {
+            let mut transcript = String::from("$ cd /\n");
+            for _ in 0..depth - 1 {
+                transcript.push_str("$ ls\ndir d\n$ cd d\n");
+            }
+            transcript.push_str("$ ls\n7 f\n");
+            transcript
+        };
+
+        let tree = discover(&nested_transcript(MAX_IMPORT_DEPTH));
+        let json = export(&tree);
+        let loaded_tree = import(&json).unwrap();
+        assert_eq!(loaded_tree.len(), MAX_IMPORT_DEPTH + 1);
+        assert_eq!(export(&loaded_tree), json);
+
+        let too_deep_tree = discover(&nested_transcript(MAX_IMPORT_DEPTH + 1));
+        assert!(matches!(
+            import(&export(&too_deep_tree)),
+            Err(JsonError::Syntax(_))
+        ));
+
+        // pretty printing would indent by gigabytes
+        const DEPTH: usize = 100_000;
+        let very_deep_tree = discover(&nested_transcript(DEPTH));
+        let json = export_compact(&very_deep_tree);
+        assert_eq!(
+            json.len(),
+            (DEPTH - 1) * r#"{"type":"directory","name":"d","children":[]}"#.len()
+                + r#"{"type":"directory","name":"/","children":[]}"#.len()
+                + r#"{"type":"file","name":"f","size":7}"#.len()
+        );
+        assert!(json.ends_with(&format!(
+            r#"{{"type":"file","name":"f","size":7}}{}"#,
+            "]}".repeat(DEPTH)
+        )));
+    }
+}
@@ -1,26 +1,148 @@
 mod discovery;
 mod filesystem_model;
 mod input;
+mod json;
+mod planner;
+mod queries;
+mod real_filesystem;
+mod report;
+mod shell;
 
-use std::fs;
+use std::path::Path;
+use std::{env, fs, io};
 
 use discovery::FilesystemDiscoverer;
 
 fn main() {
-    let input_string = fs::read_to_string("input/day7.txt").unwrap();
-    let commands = input::parse(&input_string);
+    let mut args: Vec<_> = env::args().skip(1).collect();
 
-    // this assumes that the beginning of the user history is at root
-    let mut filesystem_discoverer = FilesystemDiscoverer::start_at_root();
+    // usage: scan <directory>, writes a transcript of listing a real directory to stdout
+    if args.first().map(String::as_str) == Some("scan") {
+        let directory = args.get(1).expect("Expected a directory");
+        let tree = real_filesystem::scan(Path::new(directory)).unwrap();
 
-    for command in commands {
-        filesystem_discoverer.apply_command(&command);
+        print!("{}", real_filesystem::transcript(&tree));
+        return;
     }
 
-    let tree = filesystem_discoverer.finish();
+    // usage: from-json <file> [<mode>], uses a tree saved by to-json instead of the input
+    let tree = if args.first().map(String::as_str) == Some("from-json") {
+        let json_path = args.get(1).expect("Expected a JSON file").clone();
+        args.drain(..2);
+
+        json::import(&fs::read_to_string(json_path).unwrap())
+            .unwrap_or_else(|error| panic!("{error}"))
+    } else {
+        let input_string = fs::read_to_string("input/day7.txt").unwrap();
+        let commands = input::parse(&input_string);
+
+        // this assumes that the beginning of the user history is at root
+        let mut filesystem_discoverer = FilesystemDiscoverer::start_at_root();
+
+        for command in commands {
+            filesystem_discoverer.apply_command(&command);
+        }
+
+        // a broken history can have a diagnostic for every command
+        const MAX_PRINTED_DIAGNOSTICS: usize = 10;
+
+        let diagnostics = filesystem_discoverer.diagnostics();
+        for diagnostic in diagnostics.iter().take(MAX_PRINTED_DIAGNOSTICS) {
+            eprintln!(
+                "Warning: {}",
+                diagnostic.describe(filesystem_discoverer.tree())
+            );
+        }
+        if diagnostics.len() > MAX_PRINTED_DIAGNOSTICS {
+            eprintln!(
+                "Warning: ... and {} more warnings",
+                diagnostics.len() - MAX_PRINTED_DIAGNOSTICS
+            );
+        }
+
+        let num_unexplored_directories = filesystem_discoverer.unexplored_directory_ids().len();
+        if num_unexplored_directories > 0 {
+            eprintln!(
+                "Warning: {num_unexplored_directories} of the directories were never listed, \
+                 so some sizes are lower bounds"
+            );
+        }
+
+        filesystem_discoverer.finish()
+    };
 
     // println!("{}", tree.format_to_string());
 
+    let mode = args.first().map(String::as_str);
+
+    // usage: to-json [--compact], writes the tree to stdout
+    if mode == Some("to-json") {
+        if args.get(1).map(String::as_str) == Some("--compact") {
+            println!("{}", json::export_compact(&tree));
+        } else {
+            println!("{}", json::export(&tree));
+        }
+        return;
+    }
+
+    // usage: materialize <directory>, creates the tree with sparse files below the directory
+    if mode == Some("materialize") {
+        let target_directory = args.get(1).expect("Expected a target directory");
+        real_filesystem::materialize(&tree, Path::new(target_directory))
+            .unwrap_or_else(|error| panic!("{error}"));
+        return;
+    }
+
+    // usage: find <pattern> | resolve <path>, prints the sizes and paths of the nodes
+    if mode == Some("find") || mode == Some("resolve") {
+        let argument = args.get(1).expect("Expected a pattern or path");
+
+        let found_nodes = if mode == Some("find") {
+            tree.find(argument)
+        } else {
+            tree.resolve(argument).into_iter().collect()
+        };
+
+        for found in found_nodes {
+            println!("{}\t{}", found.size, tree.path_of(found.node.id));
+        }
+        return;
+    }
+
+    // usage: du [--depth <number>] [--top <number>] [--bar-width <number>], a report of the sizes
+    if mode == Some("du") {
+        let mut options = report::ReportOptions::default();
+
+        let mut args = args[1..].iter();
+        while let Some(arg) = args.next() {
+            let value = args
+                .next()
+                .and_then(|value| value.parse().ok())
+                .unwrap_or_else(|| panic!("Expected a number after {arg}"));
+
+            match arg.as_str() {
+                "--depth" => options.max_depth = Some(value),
+                "--top" => options.max_entries_per_directory = Some(value),
+                "--bar-width" => options.bar_width = value,
+                _ => panic!("Unknown du option {arg}"),
+            }
+        }
+
+        print!(
+            "{}",
+            report::disk_usage_report(&tree, tree.root().id, &options)
+        );
+        return;
+    }
+
+    // usage: shell, reads commands from stdin
+    if mode == Some("shell") {
+        shell::Shell::new(&tree)
+            .run(io::stdin().lock(), &mut io::stdout().lock())
+            .unwrap();
+        return;
+    }
+
     let directory_sizes = tree.recursive_directory_sizes();
 
     const SMALL_DIRECTORY_SIZE: usize = 100_000;
@@ -32,7 +154,7 @@ fn main() {
     println!("Sum of small directory sizes: {sum_of_small_directory_sizes}");
 
     const MAX_OCCUPIED_MEMORY: usize = 40_000_000;
-    let root_size = directory_sizes[&0];
+    let root_size = tree.root().size;
     let needed_memory_size = root_size.saturating_sub(MAX_OCCUPIED_MEMORY);
 
     let smallest_sufficient_directory_size = directory_sizes
@@ -44,6 +166,51 @@ fn main() {
     println!(
         "Smallest directory size that frees enough space: {smallest_sufficient_directory_size}"
     );
+
+    // usage: plan [<number of alternatives>], deletes several directories if that is smaller
+    if mode == Some("plan") {
+        let num_alternatives = args.get(1).map_or(3, |arg| {
+            arg.parse().expect("Expected a number of alternatives")
+        });
+
+        let plans = planner::plan_deletions(&tree, needed_memory_size, num_alternatives);
+
+        for (plan_idx, plan) in plans.iter().enumerate() {
+            let paths: Vec<_> = plan
+                .directory_ids
+                .iter()
+                .map(|&id| tree.path_of(id))
+                .collect();
+
+            let paths = if paths.is_empty() {
+                String::from("nothing")
+            } else {
+                paths.join(", ")
+            };
+
+            println!(
+                "Plan {}: delete {paths} with a total size of {}",
+                plan_idx + 1,
+                plan.total_size
+            );
+        }
+
+        // files directly in the root cannot be deleted with a directory
+        let Some(first_plan) = plans.first() else {
+            println!("No set of directories frees enough space");
+            return;
+        };
+
+        // remove from the back, so the ids of the other directories stay valid
+        let mut tree = tree;
+        let mut directory_ids = first_plan.directory_ids.clone();
+        directory_ids.sort_unstable();
+        for &directory_id in directory_ids.iter().rev() {
+            tree.remove_subtree(directory_id);
+        }
+
+        println!("Occupied memory after the first plan: {}", tree.root().size);
+    }
 }
 
 // count recursive directory sizes
@@ -0,0 +1,299 @@
+use std::collections::HashMap;
+
+use crate::filesystem_model::{FilesystemItem, FilesystemTree};
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct DeletionPlan {
+    pub directory_ids: Vec<usize>,
+    pub total_size: usize,
+}
+
+// the tables have one entry per total size, these bound their memory usage
+const MAX_NUM_TOTAL_SIZES: usize = 1 << 24;
+const MAX_NUM_PENDING_BITS: usize = 1 << 31;
+
+// Finds sets of directories that free at least needed_size and do not contain each other,
+// ordered by their total size. There is at most one plan per total size.
+//
+// This is a knapsack over the directories below the root in preorder: a directory is
+// either skipped, or deleted and then its whole subtree is skipped. The totals that are
+// reachable before a position only grow with the position, so remembering the first
+// position of every total is enough to reconstruct the plans.
+//
+// Large trees, like scanned real disks, would need tables with an entry per byte. There
+// the sizes are rounded down to a coarser unit, so that the tables take up a few hundred
+// MiB at most. The plans still free needed_size, but plans whose totals differ by less
+// than one unit per directory count as the same total, and plans that only barely free
+// enough space can be missed.
+pub fn plan_deletions(
+    tree: &FilesystemTree,
+    needed_size: usize,
+    num_alternatives: usize,
+) -> Vec<DeletionPlan> {
+    let (directory_ids, subtree_ends, max_depth) = directories_in_preorder(tree);
+    assert!(directory_ids.len() < u32::MAX as usize);
+    let exact_sizes: Vec<_> = directory_ids.iter().map(|&id| tree.size(id)).collect();
+
+    // every subtree that contains the current position can have a pending bitset
+    let max_num_total_sizes = MAX_NUM_TOTAL_SIZES.min(MAX_NUM_PENDING_BITS / (max_depth + 1));
+    let exact_max_total_size = max_relevant_total_size(&exact_sizes, needed_size, num_alternatives);
+    let unit = exact_max_total_size / max_num_total_sizes + 1;
+
+    let sizes: Vec<_> = exact_sizes.iter().map(|size| size / unit).collect();
+    let needed_size = needed_size.div_ceil(unit);
+    let max_total_size = max_relevant_total_size(&sizes, needed_size, num_alternatives);
+
+    // positions are stored compactly, as there can be one for every byte
+    let mut first_positions = vec![u32::MAX; max_total_size + 1];
+    first_positions[0] = 0;
+
+    let mut reachable = Bitset::new(max_total_size + 1);
+    reachable.insert(0);
+    let mut arriving: HashMap<usize, Bitset> = HashMap::new();
+
+    for position in 0..=directory_ids.len() {
+        if let Some(arriving_totals) = arriving.remove(&position) {
+            for total_size in reachable.union_with(&arriving_totals) {
+                first_positions[total_size] = position as u32;
+            }
+        }
+
+        if position < directory_ids.len() {
+            arriving
+                .entry(subtree_ends[position])
+                .or_insert_with(|| Bitset::new(max_total_size + 1))
+                .insert_shifted(&reachable, sizes[position]);
+        }
+    }
+
+    let mut positions_by_subtree_end: HashMap<usize, Vec<usize>> = HashMap::new();
+    for (position, &subtree_end) in subtree_ends.iter().enumerate() {
+        positions_by_subtree_end
+            .entry(subtree_end)
+            .or_default()
+            .push(position);
+    }
+
+    let mut plans: Vec<_> = (needed_size..=max_total_size)
+        .filter(|&total_size| first_positions[total_size] != u32::MAX)
+        .take(num_alternatives)
+        .map(|total_size| {
+            let mut plan_directory_ids = Vec::new();
+            let mut remaining_size = total_size;
+
+            // the total was first reached by deleting a directory whose subtree ends there
+            while remaining_size > 0 {
+                let first_position = first_positions[remaining_size] as usize;
+                let &position = positions_by_subtree_end[&first_position]
+                    .iter()
+                    .find(|&&position| {
+                        sizes[position] <= remaining_size
+                            && first_positions[remaining_size - sizes[position]] as usize
+                                <= position
+                    })
+                    .expect("A reachable total should have a predecessor");
+
+                plan_directory_ids.push(directory_ids[position]);
+                remaining_size -= sizes[position];
+            }
+
+            plan_directory_ids.sort_unstable();
+            DeletionPlan {
+                total_size: plan_directory_ids.iter().map(|&id| tree.size(id)).sum(),
+                directory_ids: plan_directory_ids,
+            }
+        })
+        .collect();
+
+    // only needed with a unit above one byte, where the rounding can swap totals
+    plans.sort_by_key(|plan| plan.total_size);
+    plans
+}
+
+// the ids of the directories below the root, the positions after their subtrees and
+// the deepest nesting of them
+fn directories_in_preorder(tree: &FilesystemTree) -> (Vec<usize>, Vec<usize>, usize) {
+    let mut directory_ids = Vec::new();
+    let mut subtree_ends = Vec::new();
+    let mut open_positions: Vec<(usize, usize)> = Vec::new();
+    let mut max_depth = 0;
+
+    tree.visit_nodes_from(tree.root().id, |node, depth| {
+        if let FilesystemItem::File { .. } = node.item {
+            return;
+        }
+
+        // the subtrees of all directories that are not ancestors of this one are complete
+        while let Some(&(position, open_depth)) = open_positions.last() {
+            if open_depth < depth {
+                break;
+            }
+            subtree_ends[position] = directory_ids.len();
+            open_positions.pop();
+        }
+
+        if depth > 0 {
+            open_positions.push((directory_ids.len(), depth));
+            max_depth = max_depth.max(open_positions.len());
+            directory_ids.push(node.id);
+            subtree_ends.push(0);
+        }
+    });
+
+    for (position, _) in open_positions {
+        subtree_ends[position] = directory_ids.len();
+    }
+
+    (directory_ids, subtree_ends, max_depth)
+}
+
+// The k-th smallest single directory that frees enough space bounds the k smallest plans.
+// Without enough of those, no plan can be larger than all directories together.
+fn max_relevant_total_size(sizes: &[usize], needed_size: usize, num_alternatives: usize) -> usize {
+    let mut sufficient_sizes: Vec<_> = sizes
+        .iter()
+        .copied()
+        .filter(|&size| size >= needed_size)
+        .collect();
+    sufficient_sizes.sort_unstable();
+    sufficient_sizes.dedup();
+
+    match num_alternatives.checked_sub(1) {
+        Some(idx) if idx < sufficient_sizes.len() => sufficient_sizes[idx],
+        _ => sizes.iter().sum::<usize>().max(needed_size),
+    }
+}
+
+struct Bitset {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl Bitset {
+    fn new(len: usize) -> Self {
+        Bitset {
+            words: vec![0; len.div_ceil(64)],
+            len,
+        }
+    }
+
+    fn insert(&mut self, value: usize) {
+        self.words[value / 64] |= 1 << (value % 64);
+    }
+
+    // inserts every value of other plus shift that still fits
+    fn insert_shifted(&mut self, other: &Bitset, shift: usize) {
+        let (word_shift, bit_shift) = (shift / 64, shift % 64);
+
+        for word_idx in word_shift..self.words.len() {
+            let source_idx = word_idx - word_shift;
+            let mut word = other.words[source_idx] << bit_shift;
+            if bit_shift > 0 && source_idx > 0 {
+                word |= other.words[source_idx - 1] >> (64 - bit_shift);
+            }
+            self.words[word_idx] |= word;
+        }
+
+        if !self.len.is_multiple_of(64) {
+            *self.words.last_mut().unwrap() &= (1 << (self.len % 64)) - 1;
+        }
+    }
+
+    // returns the values that were not contained before
+    fn union_with(&mut self, other: &Bitset) -> Vec<usize> {
+        let mut new_values = Vec::new();
+
+        for (word_idx, (word, &other_word)) in self.words.iter_mut().zip(&other.words).enumerate() {
+            let mut new_bits = other_word & !*word;
+            *word |= other_word;
+
+            while new_bits != 0 {
+                new_values.push(word_idx * 64 + new_bits.trailing_zeros() as usize);
+                new_bits &= new_bits - 1;
+            }
+        }
+
+        new_values
+    }
+}
+
+mod tests {
+    #[test]
+    fn siblings_beat_their_parent() {
+        use super::{plan_deletions, DeletionPlan};
+        use crate::filesystem_model::{FilesystemItem, FilesystemTree};
+
+        let directory = |name: &str| FilesystemItem::Directory {
+            name: name.to_owned(),
+        };
+        let file = |name: &str, size| FilesystemItem::File {
+            name: name.to_owned(),
+            size,
+        };
+
+        // the ids are / (0) -> a (1) -> b (2), c (3) and / -> d (4)
+        let mut tree = FilesystemTree::with_root();
+        tree.insert_child(0, directory("a"));
+        tree.insert_child(1, directory("b"));
+        tree.insert_child(1, directory("c"));
+        tree.insert_child(0, directory("d"));
+        tree.insert_child(1, file("big", 100));
+        tree.insert_child(2, file("x", 6));
+        tree.insert_child(3, file("y", 6));
+        tree.insert_child(4, file("z", 9));
+
+        assert_eq!(
+            plan_deletions(&tree, 10, 4),
+            [
+                DeletionPlan {
+                    directory_ids: vec![2, 3],
+                    total_size: 12
+                },
+                DeletionPlan {
+                    directory_ids: vec![2, 4],
+                    total_size: 15
+                },
+                DeletionPlan {
+                    directory_ids: vec![2, 3, 4],
+                    total_size: 21
+                },
+                DeletionPlan {
+                    directory_ids: vec![1],
+                    total_size: 112
+                },
+            ]
+        );
+
+        assert_eq!(tree.remove_subtree(2), 2);
+        assert_eq!(tree.recursive_directory_sizes()[&0], 115);
+        assert_eq!(
+            plan_deletions(&tree, 10, 1),
+            [DeletionPlan {
+                directory_ids: vec![2, 3],
+                total_size: 15
+            }]
+        );
+
+        // sizes of real disks are planned in coarser units, but the totals stay exact
+        const TIB: usize = 1 << 40;
+        let mut tree = FilesystemTree::with_root();
+        tree.insert_child(0, directory("a"));
+        tree.insert_child(0, directory("b"));
+        tree.insert_child(1, file("x", 3 * TIB + 1));
+        tree.insert_child(2, file("y", 5 * TIB));
+
+        assert_eq!(
+            plan_deletions(&tree, 4 * TIB, 2),
+            [
+                DeletionPlan {
+                    directory_ids: vec![2],
+                    total_size: 5 * TIB
+                },
+                DeletionPlan {
+                    directory_ids: vec![1, 2],
+                    total_size: 8 * TIB + 1
+                },
+            ]
+        );
+    }
+}
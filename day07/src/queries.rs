@@ -0,0 +1,194 @@
+use crate::filesystem_model::{FilesystemNode, FilesystemTree};
+
+// size is the recursive size for directories
+pub struct SizedNode<'a> {
+    pub node: &'a FilesystemNode,
+    pub size: usize,
+}
+
+impl FilesystemTree {
+    // absolute path, the root is /
+    pub fn path_of(&self, node_id: usize) -> String {
+        let mut names = Vec::new();
+        let mut current_node = &self.nodes()[node_id];
+
+        while let Some(parent_id) = current_node.parent_id {
+            names.push(current_node.item.name().as_str());
+            current_node = &self.nodes()[parent_id];
+        }
+
+        names.reverse();
+        format!("/{}", names.join("/"))
+    }
+
+    // absolute paths like /a/b/c.txt, . and .. are allowed as well
+    pub fn resolve(&self, path: &str) -> Option<SizedNode<'_>> {
+        let mut current_node = self.root();
+
+        for name in path
+            .split('/')
+            .filter(|name| !name.is_empty() && *name != ".")
+        {
+            current_node = match name {
+                ".." => &self.nodes()[current_node.parent_id.unwrap_or(current_node.id)],
+                _ => &self.nodes()[*current_node.child_ids.get(name)?],
+            };
+        }
+
+        Some(SizedNode {
+            node: current_node,
+            size: current_node.size,
+        })
+    }
+
+    // The pattern is matched against the whole path below the root. * and ? match within
+    // a name and ** matches any number of directories, e.g. **/*.dat or a/*/c.txt.
+    // The nodes are in the order of their paths.
+    //
+    // Every node gets the positions in the pattern that its path can have reached, so
+    // each name is matched once and subtrees without any positions are skipped.
+    pub fn find(&self, pattern: &str) -> Vec<SizedNode<'_>> {
+        let pattern_names: Vec<_> = pattern.split('/').filter(|name| !name.is_empty()).collect();
+
+        let mut matching_nodes = Vec::new();
+        let mut pending_nodes = vec![(
+            self.root().id,
+            with_skipped_wildcards(&pattern_names, vec![0]),
+        )];
+
+        while let Some((node_id, positions)) = pending_nodes.pop() {
+            let node = &self.nodes()[node_id];
+
+            if node_id != self.root().id && positions.contains(&pattern_names.len()) {
+                matching_nodes.push(SizedNode {
+                    node,
+                    size: node.size,
+                });
+            }
+
+            let mut child_ids: Vec<_> = node.child_ids.values().copied().collect();
+            child_ids.sort_by_key(|&id| self.nodes()[id].item.name());
+
+            // the first child has to be on top of the stack
+            for child_id in child_ids.into_iter().rev() {
+                let child_name = self.nodes()[child_id].item.name();
+                let child_positions = advance(&pattern_names, &positions, child_name);
+
+                if !child_positions.is_empty() {
+                    pending_nodes.push((child_id, child_positions));
+                }
+            }
+        }
+
+        matching_nodes
+    }
+}
+
+// the positions in the pattern after one more name of the path
+fn advance(pattern_names: &[&str], positions: &[usize], name: &str) -> Vec<usize> {
+    let next_positions = positions
+        .iter()
+        .filter_map(|&position| match pattern_names.get(position) {
+            Some(&"**") => Some(position),
+            Some(pattern_name) if name_matches(pattern_name, name) => Some(position + 1),
+            _ => None,
+        })
+        .collect();
+
+    with_skipped_wildcards(pattern_names, next_positions)
+}
+
+// ** can also match no directory at all, so the position after it is reached as well
+fn with_skipped_wildcards(pattern_names: &[&str], mut positions: Vec<usize>) -> Vec<usize> {
+    let mut position_idx = 0;
+    while position_idx < positions.len() {
+        let position = positions[position_idx];
+        if pattern_names.get(position) == Some(&"**") {
+            positions.push(position + 1);
+        }
+        position_idx += 1;
+    }
+
+    positions.sort_unstable();
+    positions.dedup();
+    positions
+}
+
+// Whether the name matches so far, for every prefix of the pattern. A * can match
+// nothing or extend the match of the previous name prefix by one character.
+fn name_matches(pattern: &str, name: &str) -> bool {
+    let name: Vec<_> = name.chars().collect();
+
+    let mut matches_prefix = vec![false; name.len() + 1];
+    matches_prefix[0] = true;
+
+    for pattern_char in pattern.chars() {
+        let mut next_matches_prefix = vec![false; name.len() + 1];
+
+        for name_len in 0..=name.len() {
+            next_matches_prefix[name_len] = match pattern_char {
+                '*' => {
+                    matches_prefix[name_len] || (name_len > 0 && next_matches_prefix[name_len - 1])
+                }
+                '?' => name_len > 0 && matches_prefix[name_len - 1],
+                _ => {
+                    name_len > 0
+                        && matches_prefix[name_len - 1]
+                        && name[name_len - 1] == pattern_char
+                }
+            };
+        }
+
+        matches_prefix = next_matches_prefix;
+    }
+
+    matches_prefix[name.len()]
+}
+
+mod tests {
+    #[test]
+    fn query_example_tree() {
+        use crate::discovery::example_tree;
+
+        let tree = example_tree();
+
+        let a = tree.resolve("/a").unwrap();
+        assert_eq!(a.size, 94853);
+        assert_eq!(tree.path_of(a.node.id), "/a");
+
+        let i = tree.resolve("/a/e/../e/./i").unwrap();
+        assert_eq!(i.size, 584);
+        assert_eq!(tree.path_of(i.node.id), "/a/e/i");
+        assert_eq!(tree.resolve("/").unwrap().size, 48381165);
+        assert!(tree.resolve("/a/x").is_none());
+
+        let found_paths = |pattern| {
+            tree.find(pattern)
+                .iter()
+                .map(|found| (tree.path_of(found.node.id), found.size))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            found_paths("**/?"),
+            [
+                (String::from("/a"), 94853),
+                (String::from("/a/e"), 584),
+                (String::from("/a/e/i"), 584),
+                (String::from("/a/f"), 29116),
+                (String::from("/a/g"), 2557),
+                (String::from("/d"), 24933642),
+                (String::from("/d/j"), 4060174),
+                (String::from("/d/k"), 7214296)
+            ]
+        );
+        assert_eq!(
+            found_paths("*/d.???"),
+            [
+                (String::from("/d/d.ext"), 5626152),
+                (String::from("/d/d.log"), 8033020)
+            ]
+        );
+        assert_eq!(found_paths("a/**").len(), 6);
+    }
+}
@@ -0,0 +1,191 @@
+use std::fmt::Write as _;
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::filesystem_model::{FilesystemItem, FilesystemTree};
+
+// Creates the tree below target_directory, which stands for the root. The files are
+// sparse, so they only take up space on disk for the file system's metadata.
+pub fn materialize(tree: &FilesystemTree, target_directory: &Path) -> io::Result<()> {
+    // names from JSON or a transcript could otherwise point outside of the target
+    let root_id = tree.root().id;
+    if let Some(node) = tree.nodes().iter().find(|node| {
+        let name = node.item.name();
+        node.id != root_id
+            && (name.is_empty() || name == "." || name == ".." || name.contains(['/', '\0']))
+    }) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "{:?} in {} is not a valid file name",
+                node.item.name(),
+                tree.path_of(node.parent_id.unwrap_or(root_id))
+            ),
+        ));
+    }
+
+    let mut directory_paths: Vec<PathBuf> = Vec::new();
+    let mut result = Ok(());
+
+    tree.visit_nodes_from(tree.root().id, |node, depth| {
+        if result.is_err() {
+            return;
+        }
+
+        directory_paths.truncate(depth);
+        let path = match directory_paths.last() {
+            Some(parent_path) => parent_path.join(node.item.name()),
+            None => target_directory.to_path_buf(),
+        };
+
+        result = match &node.item {
+            FilesystemItem::Directory { .. } => {
+                directory_paths.push(path.clone());
+                fs::create_dir_all(&path)
+            }
+            FilesystemItem::File { size, .. } => {
+                File::create(&path).and_then(|file| file.set_len(*size as u64))
+            }
+        };
+    });
+
+    result
+}
+
+// Symbolic links and other special files are skipped.
+pub fn scan(directory: &Path) -> io::Result<FilesystemTree> {
+    let mut tree = FilesystemTree::with_root();
+    let mut pending_directories = vec![(tree.root().id, directory.to_path_buf())];
+
+    while let Some((directory_id, path)) = pending_directories.pop() {
+        let mut entries = fs::read_dir(path)?.collect::<Result<Vec<_>, _>>()?;
+        entries.sort_by_key(|entry| entry.file_name());
+
+        for entry in entries {
+            let name = entry
+                .file_name()
+                .into_string()
+                .ok()
+                // the transcript parser skips the whitespace in front of names
+                .filter(|name| !name.contains(['\n', '\r']) && !name.starts_with([' ', '\t']))
+                .ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!(
+                            "{} cannot be written in a transcript",
+                            entry.path().display()
+                        ),
+                    )
+                })?;
+
+            let file_type = entry.file_type()?;
+
+            if file_type.is_dir() {
+                tree.insert_child(directory_id, FilesystemItem::Directory { name });
+                pending_directories.push((tree.len() - 1, entry.path()));
+            } else if file_type.is_file() {
+                let size = entry.metadata()?.len() as usize;
+                tree.insert_child(directory_id, FilesystemItem::File { name, size });
+            }
+        }
+    }
+
+    Ok(tree)
+}
+
+// the commands of a user that lists every directory once, starting at the root
+pub fn transcript(tree: &FilesystemTree) -> String {
+    let mut s = String::from("$ cd /\n");
+
+    // None stands for leaving the directory again
+    let mut pending_directory_ids = vec![Some(tree.root().id)];
+
+    while let Some(next) = pending_directory_ids.pop() {
+        let Some(directory_id) = next else {
+            s.push_str("$ cd ..\n");
+            continue;
+        };
+
+        if directory_id != tree.root().id {
+            writeln!(s, "$ cd {}", tree.nodes()[directory_id].item.name()).unwrap();
+            pending_directory_ids.push(None);
+        }
+
+        let mut child_ids: Vec<_> = tree.nodes()[directory_id]
+            .child_ids
+            .values()
+            .copied()
+            .collect();
+        child_ids.sort_by_key(|&id| tree.nodes()[id].item.name());
+
+        s.push_str("$ ls\n");
+        for &child_id in &child_ids {
+            match &tree.nodes()[child_id].item {
+                FilesystemItem::Directory { name } => writeln!(s, "dir {name}").unwrap(),
+                FilesystemItem::File { name, size } => writeln!(s, "{size} {name}").unwrap(),
+            }
+        }
+
+        pending_directory_ids.extend(
+            child_ids
+                .into_iter()
+                .rev()
+                .filter(|&id| matches!(tree.nodes()[id].item, FilesystemItem::Directory { .. }))
+                .map(Some),
+        );
+    }
+
+    s
+}
+
+mod tests {
+    #[test]
+    fn materialize_and_scan_back() {
+        use super::{materialize, scan, transcript};
+        use crate::discovery::{discover, example_tree};
+        use crate::filesystem_model::FilesystemItem;
+        use crate::json;
+
+        let tree = example_tree();
+
+        let target_directory = tempfile::tempdir().unwrap();
+        materialize(&tree, target_directory.path()).unwrap();
+
+        let d_log = target_directory.path().join("d").join("d.log");
+        assert_eq!(std::fs::metadata(d_log).unwrap().len(), 8033020);
+
+        let scanned_tree = scan(target_directory.path()).unwrap();
+        assert_eq!(json::export(&scanned_tree), json::export(&tree));
+
+        let rediscovered_tree = discover(&transcript(&scanned_tree));
+        assert_eq!(json::export(&rediscovered_tree), json::export(&tree));
+
+        for name in ["../../escaped", "..", ".", "", "a/b"] {
+            let mut tree = crate::filesystem_model::FilesystemTree::with_root();
+            tree.insert_child(
+                0,
+                FilesystemItem::Directory {
+                    name: String::from("x"),
+                },
+            );
+            tree.insert_child(
+                1,
+                FilesystemItem::File {
+                    name: String::from(name),
+                    size: 5,
+                },
+            );
+
+            let base_directory = tempfile::tempdir().unwrap();
+            let target_directory = base_directory.path().join("target");
+            assert!(materialize(&tree, &target_directory).is_err());
+            assert_eq!(std::fs::read_dir(base_directory.path()).unwrap().count(), 0);
+        }
+
+        // would lose the leading space when the transcript is read back
+        let scanned_directory = tempfile::tempdir().unwrap();
+        std::fs::write(scanned_directory.path().join(" leading space"), "").unwrap();
+        assert!(scan(scanned_directory.path()).is_err());
+    }
+}
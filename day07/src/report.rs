@@ -0,0 +1,189 @@
+use std::fmt::Write;
+
+use crate::filesystem_model::{FilesystemItem, FilesystemTree};
+
+pub struct ReportOptions {
+    // relative to the start, None shows everything
+    pub max_depth: Option<usize>,
+    // the largest entries per directory, the others are summarized in one line
+    pub max_entries_per_directory: Option<usize>,
+    pub bar_width: usize,
+}
+
+impl Default for ReportOptions {
+    fn default() -> Self {
+        ReportOptions {
+            max_depth: None,
+            max_entries_per_directory: None,
+            bar_width: 20,
+        }
+    }
+}
+
+enum Line {
+    Node {
+        id: usize,
+        depth: usize,
+        parent_size: usize,
+    },
+    HiddenEntries {
+        depth: usize,
+        num_entries: usize,
+        size: usize,
+        parent_size: usize,
+    },
+}
+
+// Like ncdu: every line has the size, the percentage of the parent and a bar for it.
+// Children are sorted by size, the largest first, and directories end with a slash.
+pub fn disk_usage_report(
+    tree: &FilesystemTree,
+    start_id: usize,
+    options: &ReportOptions,
+) -> String {
+    let mut s = String::new();
+
+    let start_size = tree.size(start_id);
+    let mut pending_lines = vec![Line::Node {
+        id: start_id,
+        depth: 0,
+        parent_size: start_size,
+    }];
+
+    while let Some(line) = pending_lines.pop() {
+        let (depth, size, parent_size, label) = match line {
+            Line::Node {
+                id,
+                depth,
+                parent_size,
+            } => {
+                let node = &tree.nodes()[id];
+                let label = match &node.item {
+                    FilesystemItem::Directory { name } if name.ends_with('/') => name.clone(),
+                    FilesystemItem::Directory { name } => format!("{name}/"),
+                    FilesystemItem::File { name, .. } => name.clone(),
+                };
+
+                if options.max_depth.is_none_or(|max_depth| depth < max_depth) {
+                    let mut child_ids: Vec<_> = node.child_ids.values().copied().collect();
+                    child_ids.sort_by_key(|&id| {
+                        (
+                            std::cmp::Reverse(tree.size(id)),
+                            tree.nodes()[id].item.name(),
+                        )
+                    });
+
+                    let num_shown = options
+                        .max_entries_per_directory
+                        .map_or(child_ids.len(), |max_entries| {
+                            max_entries.min(child_ids.len())
+                        });
+                    let hidden_ids = &child_ids[num_shown..];
+
+                    // pushed in reverse, so the largest child is printed first
+                    if !hidden_ids.is_empty() {
+                        pending_lines.push(Line::HiddenEntries {
+                            depth: depth + 1,
+                            num_entries: hidden_ids.len(),
+                            size: hidden_ids.iter().map(|&id| tree.size(id)).sum(),
+                            parent_size: node.size,
+                        });
+                    }
+                    pending_lines.extend(child_ids[..num_shown].iter().rev().map(|&id| {
+                        Line::Node {
+                            id,
+                            depth: depth + 1,
+                            parent_size: node.size,
+                        }
+                    }));
+                }
+
+                (depth, node.size, parent_size, label)
+            }
+            Line::HiddenEntries {
+                depth,
+                num_entries,
+                size,
+                parent_size,
+            } => (
+                depth,
+                size,
+                parent_size,
+                format!("({num_entries} more entries)"),
+            ),
+        };
+
+        let fraction = if parent_size == 0 {
+            0.0
+        } else {
+            size as f64 / parent_size as f64
+        };
+        let bar_len = (fraction * options.bar_width as f64).round() as usize;
+
+        writeln!(
+            s,
+            "{:>10} {:>5.1}% [{:<bar_width$}] {:indent$}{label}",
+            human_readable_size(size),
+            fraction * 100.0,
+            "#".repeat(bar_len),
+            "",
+            bar_width = options.bar_width,
+            indent = depth * 2,
+        )
+        .unwrap();
+    }
+
+    s
+}
+
+// binary units with one decimal, bytes are exact
+fn human_readable_size(size: usize) -> String {
+    const UNITS: [&str; 6] = ["B", "KiB", "MiB", "GiB", "TiB", "PiB"];
+
+    let mut scaled_size = size as f64;
+    let mut unit_idx = 0;
+    while scaled_size >= 1024.0 && unit_idx + 1 < UNITS.len() {
+        scaled_size /= 1024.0;
+        unit_idx += 1;
+    }
+
+    if unit_idx == 0 {
+        format!("{size} B")
+    } else {
+        format!("{scaled_size:.1} {}", UNITS[unit_idx])
+    }
+}
+
+mod tests {
+    #[test]
+    fn report_of_example_tree() {
+        use super::{disk_usage_report, human_readable_size, ReportOptions};
+        use crate::discovery::example_tree;
+
+        assert_eq!(human_readable_size(1023), "1023 B");
+        assert_eq!(human_readable_size(1536), "1.5 KiB");
+        assert_eq!(human_readable_size(24933642), "23.8 MiB");
+
+        let tree = example_tree();
+
+        let options = ReportOptions {
+            max_depth: Some(2),
+            max_entries_per_directory: Some(2),
+            bar_width: 10,
+        };
+
+        let report = disk_usage_report(&tree, 0, &options);
+        assert_eq!(
+            report.lines().collect::<Vec<_>>(),
+            [
+                "  46.1 MiB 100.0% [##########] /",
+                "  23.8 MiB  51.5% [#####     ]   d/",
+                "   7.7 MiB  32.2% [###       ]     d.log",
+                "   6.9 MiB  28.9% [###       ]     k",
+                "   9.2 MiB  38.8% [####      ]     (2 more entries)",
+                "  14.2 MiB  30.7% [###       ]   b.txt",
+                "   8.2 MiB  17.8% [##        ]   (2 more entries)",
+            ]
+        );
+    }
+}
@@ -0,0 +1,178 @@
+use std::cmp::Ordering;
+use std::io::{self, BufRead, Write};
+
+use crate::filesystem_model::{FilesystemItem, FilesystemNode, FilesystemTree, RelativeDirectory};
+
+// Explores a discovered tree with a few commands of a real shell:
+// cd <dir> | ls | pwd | tree | du | find -size <+N, -N or N>
+pub struct Shell<'a> {
+    tree: &'a FilesystemTree,
+    current_directory_id: usize,
+}
+
+impl<'a> Shell<'a> {
+    pub fn new(tree: &'a FilesystemTree) -> Self {
+        Shell {
+            tree,
+            current_directory_id: tree.root().id,
+        }
+    }
+
+    pub fn run<R: BufRead, W: Write>(&mut self, input: R, out: &mut W) -> io::Result<()> {
+        write!(out, "{} $ ", self.tree.path_of(self.current_directory_id))?;
+        out.flush()?;
+
+        for line in input.lines() {
+            self.execute(&line?, out)?;
+
+            write!(out, "{} $ ", self.tree.path_of(self.current_directory_id))?;
+            out.flush()?;
+        }
+
+        writeln!(out)
+    }
+
+    fn execute<W: Write>(&mut self, line: &str, out: &mut W) -> io::Result<()> {
+        // the rest of the line is the target, so names with spaces can be entered
+        if let Some(target) = line.trim_start().strip_prefix("cd ") {
+            let target = target.trim_start();
+
+            return match self
+                .tree
+                .relative_directory_id(self.current_directory_id, &RelativeDirectory::from(target))
+            {
+                Some(directory_id) => {
+                    self.current_directory_id = directory_id;
+                    Ok(())
+                }
+                None => writeln!(out, "cd: no such directory: {target}"),
+            };
+        }
+
+        let words: Vec<_> = line.split_whitespace().collect();
+
+        match words.as_slice() {
+            [] => Ok(()),
+            ["ls"] => {
+                let current_directory = &self.tree.nodes()[self.current_directory_id];
+                let mut child_ids: Vec<_> = current_directory.child_ids.values().collect();
+                child_ids.sort_by_key(|&&id| self.tree.nodes()[id].item.name());
+
+                for &child_id in child_ids {
+                    writeln!(out, "{}", self.tree.nodes()[child_id].item)?;
+                }
+                Ok(())
+            }
+            ["pwd"] => writeln!(out, "{}", self.tree.path_of(self.current_directory_id)),
+            ["tree"] => write!(
+                out,
+                "{}",
+                self.tree
+                    .format_subtree_to_string(self.current_directory_id)
+            ),
+            ["du"] => {
+                let mut lines = Vec::new();
+                self.visit_with_paths(|node, path| {
+                    if let FilesystemItem::Directory { .. } = node.item {
+                        lines.push(format!("{}\t{path}", node.size));
+                    }
+                });
+
+                for line in lines {
+                    writeln!(out, "{line}")?;
+                }
+                Ok(())
+            }
+            ["find", "-size", size_filter] => {
+                // like in find, +N means larger, -N smaller and N exactly N
+                let (ordering, threshold) = if let Some(threshold) = size_filter.strip_prefix('+') {
+                    (Ordering::Greater, threshold)
+                } else if let Some(threshold) = size_filter.strip_prefix('-') {
+                    (Ordering::Less, threshold)
+                } else {
+                    (Ordering::Equal, *size_filter)
+                };
+
+                let Ok(threshold) = threshold.parse::<usize>() else {
+                    return writeln!(out, "find: expected a size like +1000, -1000 or 1000");
+                };
+
+                let mut lines = Vec::new();
+                self.visit_with_paths(|node, path| {
+                    if node.size.cmp(&threshold) == ordering {
+                        lines.push(format!("{}\t{path}", node.size));
+                    }
+                });
+
+                for line in lines {
+                    writeln!(out, "{line}")?;
+                }
+                Ok(())
+            }
+            _ => writeln!(out, "unknown command: {line}"),
+        }
+    }
+
+    // the path is extended while walking down instead of walking up from every node
+    fn visit_with_paths<F>(&self, mut f: F)
+    where
+        F: FnMut(&FilesystemNode, &str),
+    {
+        let mut path = self.tree.path_of(self.current_directory_id);
+        let mut path_lengths = Vec::new();
+
+        self.tree
+            .visit_nodes_from(self.current_directory_id, |node, depth| {
+                if depth > 0 {
+                    path.truncate(path_lengths[depth - 1]);
+                    if !path.ends_with('/') {
+                        path.push('/');
+                    }
+                    path.push_str(node.item.name());
+                }
+
+                path_lengths.truncate(depth);
+                path_lengths.push(path.len());
+                f(node, &path);
+            });
+    }
+}
+
+mod tests {
+    #[test]
+    fn explore_example_tree() {
+        use super::Shell;
+        use crate::discovery::{discover, example_tree};
+
+        let tree = example_tree();
+
+        let commands = "cd a\nls\npwd\ncd e\ndu\ncd /\nfind -size +10000000\n\
+                        find -size 2557\nfind -size x\ncd b.txt\n";
+        let mut out = Vec::new();
+        Shell::new(&tree)
+            .run(commands.as_bytes(), &mut out)
+            .unwrap();
+
+        let out = String::from_utf8(out).unwrap();
+        let expected = "/ $ /a $ e (dir)\nf (file, size=29116)\ng (file, size=2557)\n\
+                        h.lst (file, size=62596)\n/a $ /a\n/a $ /a/e $ 584\t/a/e\n/a/e $ \
+                        / $ 48381165\t/\n14848514\t/b.txt\n24933642\t/d\n\
+                        / $ 2557\t/a/g\n/ $ find: expected a size like +1000, -1000 or 1000\n\
+                        / $ cd: no such directory: b.txt\n/ $ \n";
+        assert_eq!(out, expected);
+
+        let tree = discover("$ cd /\n$ ls\ndir my dir\n$ cd my dir\n$ ls\n5 a b\n");
+        let mut out = Vec::new();
+        Shell::new(&tree)
+            .run(
+                "cd my dir\npwd\ncd /\ndu\nfind -size 5\n".as_bytes(),
+                &mut out,
+            )
+            .unwrap();
+
+        let out = String::from_utf8(out).unwrap();
+        let expected = "/ $ /my dir $ /my dir\n/my dir $ / $ 5\t/\n5\t/my dir\n\
+                        / $ 5\t/\n5\t/my dir\n5\t/my dir/a b\n/ $ \n";
+        assert_eq!(out, expected);
+    }
+}